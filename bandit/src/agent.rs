@@ -0,0 +1,220 @@
+use std::vec::Vec;
+use statrs::distribution::Beta;
+use rand::distributions::Distribution;
+use rand::{Rng, RngCore};
+use crate::pareto::{ParetoUcb, ScalarizedUcb, Scalarization};
+use crate::risk::{CvarUcb, Marab, MeanVarianceUcb};
+use crate::robust::RobustUcb;
+
+
+pub trait Agent {
+    fn name(&self) -> String;
+    fn probs(&mut self, rng: &mut dyn RngCore) -> Vec<f64>;
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        let probs = self.probs(rng);
+        sample_index(&probs, rng)
+    }
+    fn update(&mut self, arm: usize, reward: f64);
+    fn update_vector(&mut self, arm: usize, reward: &[f64]) {
+        self.update(arm, reward[0])
+    }
+    fn observe(&mut self, _arm: usize, _reward: f64) {}
+    fn reset(&mut self);
+}
+
+pub fn sample_index(probs: &[f64], rng: &mut dyn RngCore) -> usize {
+    let mut u = rng.gen::<f64>() * probs.iter().sum::<f64>();
+    for (i, p) in probs.iter().enumerate() {
+        if u < *p {
+            return i
+        }
+        u -= p;
+    }
+    probs.len() - 1
+}
+
+pub fn argmax(values: &[f64]) -> usize {
+    let mut best = 0;
+    for (i, v) in values.iter().enumerate() {
+        if *v > values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+pub fn one_hot(n: usize, index: usize) -> Vec<f64> {
+    let mut v = vec![0.; n];
+    v[index] = 1.;
+    v
+}
+
+
+#[derive(Debug, Clone)]
+pub struct ArmStats {
+    pub counts: Vec<usize>,
+    pub sums: Vec<f64>,
+}
+
+impl ArmStats {
+    pub fn new(n_arms: usize) -> ArmStats {
+        ArmStats { counts: vec![0; n_arms], sums: vec![0.; n_arms] }
+    }
+    pub fn n_arms(&self) -> usize {
+        self.counts.len()
+    }
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+    pub fn mean(&self, arm: usize) -> f64 {
+        if self.counts[arm] == 0 { 0. } else { self.sums[arm] / self.counts[arm] as f64 }
+    }
+    pub fn means(&self) -> Vec<f64> {
+        (0..self.n_arms()).map(|i| self.mean(i)).collect()
+    }
+    pub fn unplayed(&self) -> Option<usize> {
+        self.counts.iter().position(|c| *c == 0)
+    }
+    pub fn update(&mut self, arm: usize, reward: f64) {
+        self.counts[arm] += 1;
+        self.sums[arm] += reward;
+    }
+    pub fn reset(&mut self) {
+        *self = ArmStats::new(self.n_arms());
+    }
+}
+
+
+pub struct EpsilonGreedy {
+    epsilon: f64,
+    stats: ArmStats,
+}
+
+impl EpsilonGreedy {
+    pub fn new(n_arms: usize, epsilon: f64) -> EpsilonGreedy {
+        EpsilonGreedy { epsilon, stats: ArmStats::new(n_arms) }
+    }
+}
+
+impl Agent for EpsilonGreedy {
+    fn name(&self) -> String {
+        format!("epsilon-greedy({})", self.epsilon)
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        let n = self.stats.n_arms();
+        let best = argmax(&self.stats.means());
+        (0..n).map(|i| {
+            let explore = self.epsilon / n as f64;
+            if i == best { explore + 1. - self.epsilon } else { explore }
+        }).collect()
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.stats.update(arm, reward)
+    }
+    fn reset(&mut self) {
+        self.stats.reset()
+    }
+}
+
+
+pub struct Ucb1 {
+    stats: ArmStats,
+}
+
+impl Ucb1 {
+    pub fn new(n_arms: usize) -> Ucb1 {
+        Ucb1 { stats: ArmStats::new(n_arms) }
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        let total = self.stats.total().max(1) as f64;
+        (0..self.stats.n_arms()).map(|i| match self.stats.counts[i] {
+            0 => f64::INFINITY,
+            n => self.stats.mean(i) + (2. * total.ln() / n as f64).sqrt(),
+        }).collect()
+    }
+}
+
+impl Agent for Ucb1 {
+    fn name(&self) -> String {
+        "ucb1".to_string()
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        one_hot(self.stats.n_arms(), argmax(&self.indices()))
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        argmax(&self.indices())
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.stats.update(arm, reward)
+    }
+    fn reset(&mut self) {
+        self.stats.reset()
+    }
+}
+
+
+pub struct Thompson {
+    alpha: Vec<f64>,
+    beta: Vec<f64>,
+    n_samples: usize,
+}
+
+impl Thompson {
+    pub fn new(n_arms: usize) -> Thompson {
+        Thompson { alpha: vec![1.; n_arms], beta: vec![1.; n_arms], n_samples: 1000 }
+    }
+    pub fn sample(&self, rng: &mut dyn RngCore) -> Vec<f64> {
+        self.alpha.iter().zip(self.beta.iter())
+            .map(|(a, b)| Beta::new(*a, *b).unwrap().sample(rng))
+            .collect()
+    }
+}
+
+impl Agent for Thompson {
+    fn name(&self) -> String {
+        "thompson".to_string()
+    }
+    fn probs(&mut self, rng: &mut dyn RngCore) -> Vec<f64> {
+        let mut probs = vec![0.; self.alpha.len()];
+        for _ in 0..self.n_samples {
+            probs[argmax(&self.sample(rng))] += 1. / self.n_samples as f64;
+        }
+        probs
+    }
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        argmax(&self.sample(rng))
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        let r = reward.clamp(0., 1.);
+        self.alpha[arm] += r;
+        self.beta[arm] += 1. - r;
+    }
+    fn reset(&mut self) {
+        let n = self.alpha.len();
+        self.alpha = vec![1.; n];
+        self.beta = vec![1.; n];
+    }
+}
+
+
+pub fn by_name(name: &str, n_arms: usize) -> Result<Box<dyn Agent + Send>, String> {
+    let mut parts = name.split(':');
+    let kind = parts.next().unwrap_or("");
+    let params = parts.map(|x| x.parse::<f64>().map_err(|e| format!("{}: {}", name, e))).collect::<Result<Vec<f64>, String>>()?;
+    let param = |i: usize, default: f64| params.get(i).cloned().unwrap_or(default);
+    match kind {
+        "ucb1" => Ok(Box::new(Ucb1::new(n_arms))),
+        "thompson" => Ok(Box::new(Thompson::new(n_arms))),
+        "egreedy" | "epsilon-greedy" => Ok(Box::new(EpsilonGreedy::new(n_arms, param(0, 0.1)))),
+        "mom-ucb" => Ok(Box::new(RobustUcb::median_of_means(n_arms, param(0, 1.), param(1, 1.)))),
+        "trunc-ucb" => Ok(Box::new(RobustUcb::truncated_mean(n_arms, param(0, 1.), param(1, 1.)))),
+        "catoni-ucb" => Ok(Box::new(RobustUcb::catoni(n_arms, param(0, 1.)))),
+        "marab" => Ok(Box::new(Marab::new(n_arms, param(0, 0.05), param(1, 1.)))),
+        "cvar-ucb" => Ok(Box::new(CvarUcb::new(n_arms, param(0, 0.05), param(1, 1.)))),
+        "mv-ucb" => Ok(Box::new(MeanVarianceUcb::new(n_arms, param(0, 1.)))),
+        "pareto-ucb" => Ok(Box::new(ParetoUcb::new(n_arms))),
+        "linear-ucb" => Ok(Box::new(ScalarizedUcb::new(n_arms, Scalarization::Linear(params.clone())))),
+        "chebyshev-ucb" => Ok(Box::new(ScalarizedUcb::new(n_arms, Scalarization::Chebyshev(params.clone())))),
+        _ => Err(format!("Unknown agent: {}", name))
+    }
+}
@@ -0,0 +1,50 @@
+use std::vec::Vec;
+use serde::Serialize;
+use crate::agent::argmax;
+use crate::game::Game;
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Counterfactual {
+    pub actions: Vec<usize>,
+    pub rewards: Vec<f64>,
+    pub arm_rewards: Vec<Vec<f64>>,
+    pub arm_totals: Vec<f64>,
+    pub best_arm: usize,
+    pub best_total: f64,
+    pub score: f64,
+    pub regret: f64,
+}
+
+impl Counterfactual {
+    pub fn step_regret(&self) -> Vec<f64> {
+        self.rewards.iter().enumerate()
+            .map(|(t, r)| self.arm_rewards[self.best_arm][t] - r)
+            .collect()
+    }
+}
+
+pub fn counterfactual(game: &Game) -> Result<Counterfactual, String> {
+    if game.is_playing() {
+        return Err("The episode is not finished yet.".to_string())
+    }
+    if game.history().is_empty() {
+        return Err("The episode has no plays.".to_string())
+    }
+    let actions: Vec<usize> = game.history().iter().map(|(a, _)| *a).collect();
+    let rewards: Vec<f64> = game.history().iter().map(|(_, r)| *r).collect();
+    let arm_rewards = game.counterfactual_rewards();
+    let arm_totals: Vec<f64> = arm_rewards.iter().map(|x| x.iter().sum()).collect();
+    let best_arm = argmax(&arm_totals);
+    let score: f64 = rewards.iter().sum();
+    Ok(Counterfactual {
+        actions,
+        rewards,
+        best_total: arm_totals[best_arm],
+        regret: arm_totals[best_arm] - score,
+        arm_rewards,
+        arm_totals,
+        best_arm,
+        score,
+    })
+}
@@ -1,48 +1,566 @@
 
-use std::sync::Mutex;
-use crate::game::Game;
-use rocket::State;
+use std::env;
+use std::io::Cursor;
+use std::net::SocketAddr;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::game::{EpisodeMode, Game, RewardType};
+use rocket::{Data, Outcome, Request, Response, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Status};
+use rocket::request::{self, FromRequest};
+use rocket::response::{self, Responder};
+use rocket::response::content::{Html, Json, Plain};
+use rocket::response::status::Custom;
+use crate::analysis;
+use crate::hint;
+use crate::continuous::ContinuousGame;
+use crate::room::{Collision, Room, Rooms};
+use crate::risk::Objective;
+use crate::metrics::Metrics;
+use crate::audit::{Action, AuditLog, Setup};
+use crate::registry::parse_params;
+use crate::keys::{Charge, Denied, KeyStore, Limits};
+
+struct Timer(Arc<Metrics>);
+
+struct Started(Option<Instant>);
+
+impl Fairing for Timer {
+    fn info(&self) -> Info {
+        Info { name: "Request Metrics", kind: Kind::Request | Kind::Response }
+    }
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        request.local_cache(|| Started(Some(Instant::now())));
+    }
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if let Some(started) = request.local_cache(|| Started(None)).0 {
+            let route = request.route().map(|r| r.uri.path().to_string()).unwrap_or("unmatched".to_string());
+            self.0.record(&route, response.status().code, started.elapsed());
+        }
+    }
+}
+
+#[get("/")]
+fn ui() -> Html<&'static str> {
+    Html(include_str!("../static/index.html"))
+}
+
+struct AdminToken(Option<String>);
+
+struct Admin;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+    type Error = String;
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Admin, String> {
+        let expected = match request.guard::<State<AdminToken>>() {
+            Outcome::Success(token) => token.0.clone(),
+            _ => None,
+        };
+        let given = request.headers().get_one("X-Admin-Token");
+        match (expected, given) {
+            (None, _) => Outcome::Failure((Status::Forbidden, "The admin API is disabled.".to_string())),
+            (Some(expected), Some(given)) if same(expected.as_bytes(), given.as_bytes()) => Outcome::Success(Admin),
+            _ => Outcome::Failure((Status::Unauthorized, "Invalid admin token.".to_string())),
+        }
+    }
+}
+
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn with_game<T, F>(session: &str, game: &Mutex<Game>, rooms: &Rooms, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Game) -> Result<T, String>
+{
+    if session == "game" {
+        return f(&mut *game.lock().unwrap())
+    }
+    match session.strip_prefix("room:") {
+        Some(id) => match rooms.lock().unwrap().get_mut(id) {
+            Some(room) => f(room.game_mut()),
+            None => Err(format!("No room {}", id))
+        },
+        None => Err(format!("No session {}", session))
+    }
+}
+
+fn bad(e: String) -> Custom<String> {
+    Custom(Status::BadRequest, e)
+}
+
+fn missing(e: String) -> Custom<String> {
+    Custom(Status::NotFound, e)
+}
+
+fn broken(e: String) -> Custom<String> {
+    Custom(Status::InternalServerError, e)
+}
+
+fn bad_json(e: String) -> Custom<Json<String>> {
+    Custom(Status::BadRequest, Json(serde_json::json!({ "error": e }).to_string()))
+}
+
+fn missing_json(e: String) -> Custom<Json<String>> {
+    Custom(Status::NotFound, Json(serde_json::json!({ "error": e }).to_string()))
+}
+
+struct Rejection(Option<Denied>);
+
+struct Rejected(Status, Option<Denied>);
+
+impl<'r> Responder<'r> for Rejected {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        let message = self.1.as_ref().map(|d| d.to_string()).unwrap_or(self.0.reason.to_string());
+        let mut response = Response::build();
+        response.status(self.0).header(ContentType::Plain).sized_body(Cursor::new(message));
+        if let Some(retry_after) = self.1.and_then(|d| d.retry_after()) {
+            response.raw_header("Retry-After", retry_after.to_string());
+        }
+        response.ok()
+    }
+}
+
+fn denial_status(denied: &Denied) -> Status {
+    match denied {
+        Denied::MissingKey | Denied::UnknownKey => Status::Unauthorized,
+        _ => Status::TooManyRequests,
+    }
+}
+
+fn rejected(denied: Denied) -> Rejected {
+    Rejected(denial_status(&denied), Some(denied))
+}
+
+fn charge(request: &Request, kind: Charge) -> request::Outcome<(), Denied> {
+    let keys = match request.guard::<State<KeyStore>>() {
+        Outcome::Success(keys) => keys,
+        _ => return Outcome::Success(()),
+    };
+    match keys.charge(request.headers().get_one("X-Api-Key"), kind, Instant::now()) {
+        Ok(()) => {
+            request.local_cache(|| Charged(Some(kind)));
+            Outcome::Success(())
+        }
+        Err(denied) => {
+            request.local_cache(|| Rejection(Some(denied.clone())));
+            Outcome::Failure((denial_status(&denied), denied))
+        }
+    }
+}
+
+struct Charged(Option<Charge>);
+
+struct Refunds;
+
+impl Fairing for Refunds {
+    fn info(&self) -> Info {
+        Info { name: "Quota Refunds", kind: Kind::Response }
+    }
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if response.status().code < 400 {
+            return
+        }
+        if let (Some(kind), Outcome::Success(keys)) = (request.local_cache(|| Charged(None)).0, request.guard::<State<KeyStore>>()) {
+            keys.refund(request.headers().get_one("X-Api-Key"), kind, Instant::now());
+        }
+    }
+}
+
+struct Pull;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Pull {
+    type Error = Denied;
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Pull, Denied> {
+        charge(request, Charge::Pull).map(|_| Pull)
+    }
+}
+
+struct NewGame;
+
+impl<'a, 'r> FromRequest<'a, 'r> for NewGame {
+    type Error = Denied;
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<NewGame, Denied> {
+        charge(request, Charge::Game).map(|_| NewGame)
+    }
+}
+
+struct ApiKey(String);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ApiKey {
+    type Error = Denied;
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<ApiKey, Denied> {
+        match request.headers().get_one("X-Api-Key") {
+            Some(key) => Outcome::Success(ApiKey(key.to_string())),
+            None => {
+                request.local_cache(|| Rejection(Some(Denied::MissingKey)));
+                Outcome::Failure((Status::Unauthorized, Denied::MissingKey))
+            }
+        }
+    }
+}
+
+#[catch(401)]
+fn unauthorized(request: &Request) -> Rejected {
+    Rejected(Status::Unauthorized, request.local_cache(|| Rejection(None)).0.clone())
+}
+
+#[catch(429)]
+fn too_many_requests(request: &Request) -> Rejected {
+    Rejected(Status::TooManyRequests, request.local_cache(|| Rejection(None)).0.clone())
+}
+
+#[get("/register/<name>")]
+fn register_key(name: String, remote: SocketAddr, keys: State<KeyStore>) -> Result<Json<String>, Rejected> {
+    let key = keys.register(&name, Some(remote.ip()), Instant::now()).map_err(rejected)?;
+    Ok(Json(serde_json::json!({ "name": name, "key": key, "limits": keys.limits() }).to_string()))
+}
+
+#[get("/usage")]
+fn key_usage(key: ApiKey, keys: State<KeyStore>) -> Result<Json<String>, Rejected> {
+    let usage = keys.usage(&key.0, Instant::now()).map_err(rejected)?;
+    Ok(Json(serde_json::to_string(&usage).unwrap()))
+}
+
+#[get("/healthz")]
+fn healthz(game: State<Mutex<Game>>, continuous: State<Mutex<ContinuousGame>>, rooms: State<Rooms>) -> Result<&'static str, Status> {
+    if game.lock().is_ok() && continuous.lock().is_ok() && rooms.lock().is_ok() {
+        Ok("ok")
+    } else {
+        Err(Status::ServiceUnavailable)
+    }
+}
+
+#[get("/metrics")]
+fn prometheus(metrics: State<Arc<Metrics>>, game: State<Mutex<Game>>, continuous: State<Mutex<ContinuousGame>>, rooms: State<Rooms>) -> Plain<String> {
+    let sessions = [
+        ("game", game.lock().unwrap().is_playing() as usize),
+        ("continuous", continuous.lock().unwrap().is_playing() as usize),
+        ("room", rooms.lock().unwrap().values().filter(|r| !r.is_finished()).count()),
+    ];
+    Plain(metrics.render(&sessions))
+}
 
 #[get("/")]
 fn desc() -> &'static str {
     "You can play games"
 }
 
-#[get("/score")]
-fn score(state: State<Mutex<Game>>) -> String {
+#[get("/score?<objective>")]
+fn score(objective: Option<String>, state: State<Mutex<Game>>) -> Result<String, Custom<String>> {
     let game = state.lock().unwrap();
-    format!(
-        "Your Score: {}\nPlay Count: {}\nMean Score: {}",
+    if let Some(objective) = objective {
+        let objective = objective.parse::<Objective>().map_err(bad)?;
+        return Ok(format!("{}: {}", objective, game.score_with(&objective)))
+    }
+    Ok(format!(
+        "Your Score: {}\nPlay Count: {}\nMean Score: {}\nHints Used: {}",
         game.score(),
         game.play_count(),
-        game.score() / (game.play_count() as f64)
-    ) 
+        game.score() / (game.play_count() as f64),
+        game.hints_used()
+    ))
 }
 
 #[get("/reset")]
-fn reset(state: State<Mutex<Game>>) -> String {
-    unimplemented!()
+fn reset(state: State<Mutex<Game>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut game = state.lock().unwrap();
+    game.reset();
+    audit.append("game", Action::Reset).map_err(broken)?;
+    Ok("Game reset".to_string())
 }
 
-#[get("/start")]
-fn start(state: State<Mutex<Game>>) -> String {
+#[get("/start?<mode>&<hints>&<n_games>")]
+fn start(_quota: NewGame, mode: Option<String>, hints: Option<bool>, n_games: Option<usize>, state: State<Mutex<Game>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
     let mut game = state.lock().unwrap();
-    game.start(Option::None);
-    "Game start".to_string()
+    if let Some(mode) = mode {
+        game.set_mode(mode.parse::<EpisodeMode>().map_err(bad)?);
+    }
+    game.set_hints(hints.unwrap_or(false));
+    game.start(n_games);
+    audit.append("game", Action::Start { n_games, mode: game.mode() }).map_err(broken)?;
+    Ok("Game start".to_string())
 }
 
 #[get("/play/<index>")]
-fn play(index: usize, state: State<Mutex<Game>>) -> String {
+fn play(_quota: Pull, index: usize, state: State<Mutex<Game>>, metrics: State<Arc<Metrics>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut game = state.lock().unwrap();
+    let val = game.play(index).map_err(bad)?;
+    audit.append("game", Action::Play { arm: index, reward: val }).map_err(broken)?;
+    metrics.pull();
+    Ok(format!("Won {}", val))
+}
+
+#[get("/finish")]
+fn finish(state: State<Mutex<Game>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
     let mut game = state.lock().unwrap();
-    match game.play(index) {
-        Ok(val) => format!("Won {}", val),
-        Err(val) => val 
+    game.finish();
+    audit.append("game", Action::Finish).map_err(broken)?;
+    Ok("Game finished".to_string())
+}
+
+#[get("/hint?<agent>")]
+fn suggest(agent: Option<String>, state: State<Mutex<Game>>) -> Result<Json<String>, Custom<Json<String>>> {
+    let mut game = state.lock().unwrap();
+    let agent = agent.unwrap_or("ucb1".to_string());
+    let result = hint::hint(&mut game, &agent, &mut rand::thread_rng()).map_err(bad_json)?;
+    Ok(Json(serde_json::to_string(&result).unwrap()))
+}
+
+#[get("/state")]
+fn status(state: State<Mutex<Game>>) -> Json<String> {
+    let game = state.lock().unwrap();
+    Json(serde_json::json!({
+        "n_arms": game.n_machines(),
+        "playing": game.is_playing(),
+        "horizon": game.limit(),
+        "score": game.score(),
+        "history": game.history(),
+    }).to_string())
+}
+
+#[get("/analysis")]
+fn analyze(state: State<Mutex<Game>>) -> Result<Json<String>, Custom<Json<String>>> {
+    let game = state.lock().unwrap();
+    let result = analysis::counterfactual(&game).map_err(bad_json)?;
+    Ok(Json(serde_json::to_string(&result).unwrap()))
+}
+
+#[get("/")]
+fn desc_continuous(state: State<Mutex<ContinuousGame>>) -> String {
+    let game = state.lock().unwrap();
+    let domain = game.domain();
+    format!("Pick a point x in {:?}..{:?}, e.g. /continuous/play?x={}", domain.lower, domain.upper,
+        domain.center().iter().map(|v| v.to_string()).collect::<Vec<String>>().join(","))
+}
+
+#[get("/start?<horizon>")]
+fn start_continuous(_quota: NewGame, horizon: Option<usize>, state: State<Mutex<ContinuousGame>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut game = state.lock().unwrap();
+    game.start(horizon);
+    audit.append("continuous", Action::Begin { horizon }).map_err(broken)?;
+    Ok("Game start".to_string())
+}
+
+#[get("/play?<x>")]
+fn play_continuous(_quota: Pull, x: String, state: State<Mutex<ContinuousGame>>, metrics: State<Arc<Metrics>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut game = state.lock().unwrap();
+    let point = game.domain().parse_point(&x).map_err(bad)?;
+    let val = game.play(&point).map_err(bad)?;
+    audit.append("continuous", Action::Probe { x: point, reward: val }).map_err(broken)?;
+    metrics.pull();
+    Ok(format!("Won {}", val))
+}
+
+#[get("/score")]
+fn score_continuous(state: State<Mutex<ContinuousGame>>) -> String {
+    let game = state.lock().unwrap();
+    let count = game.history().len();
+    format!(
+        "Your Score: {}\nPlay Count: {}\nMean Score: {}",
+        game.score(),
+        count,
+        game.score() / (count as f64)
+    )
+}
+
+#[get("/finish")]
+fn finish_continuous(state: State<Mutex<ContinuousGame>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut game = state.lock().unwrap();
+    game.finish();
+    audit.append("continuous", Action::Finish).map_err(broken)?;
+    Ok("Game finished".to_string())
+}
+
+#[get("/sessions")]
+fn admin_sessions(_admin: Admin, game: State<Mutex<Game>>, continuous: State<Mutex<ContinuousGame>>, rooms: State<Rooms>) -> Json<String> {
+    let mut sessions = vec![];
+    {
+        let game = game.lock().unwrap();
+        sessions.push(serde_json::json!({
+            "id": "game",
+            "playing": game.is_playing(),
+            "arms": game.n_machines(),
+            "plays": game.history().len(),
+            "score": game.score(),
+        }));
+    }
+    {
+        let continuous = continuous.lock().unwrap();
+        sessions.push(serde_json::json!({
+            "id": "continuous",
+            "playing": continuous.is_playing(),
+            "dim": continuous.domain().dim(),
+            "plays": continuous.history().len(),
+            "score": continuous.score(),
+        }));
+    }
+    let rooms = rooms.lock().unwrap();
+    let mut ids: Vec<&String> = rooms.keys().collect();
+    ids.sort();
+    for id in ids {
+        let room = &rooms[id];
+        sessions.push(serde_json::json!({
+            "id": format!("room:{}", id),
+            "playing": !room.is_finished(),
+            "arms": room.n_machines(),
+            "players": room.n_players(),
+            "round": room.round(),
+        }));
     }
+    Json(serde_json::Value::from(sessions).to_string())
 }
 
+#[get("/<session>/profiles")]
+fn admin_profiles(_admin: Admin, session: String, game: State<Mutex<Game>>, rooms: State<Rooms>) -> Result<Json<String>, Custom<Json<String>>> {
+    let value = with_game(&session, &game, &rooms, |game| {
+        let stored = (0..game.n_machines()).map(|i| game.stored_profiles(i)).collect::<Result<Vec<_>, String>>()?;
+        Ok(serde_json::json!({ "profiles": game.profiles(), "stored": stored }))
+    }).map_err(bad_json)?;
+    Ok(Json(value.to_string()))
+}
+
+#[get("/<session>/finish")]
+fn admin_finish(_admin: Admin, session: String, game: State<Mutex<Game>>, continuous: State<Mutex<ContinuousGame>>, rooms: State<Rooms>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let result = if session == "continuous" {
+        continuous.lock().unwrap().finish();
+        Ok(())
+    } else if let Some(id) = session.strip_prefix("room:") {
+        rooms.lock().unwrap().get_mut(id).map(|room| room.finish()).ok_or(format!("No room {}", id))
+    } else {
+        with_game(&session, &game, &rooms, |game| {
+            game.finish();
+            Ok(())
+        })
+    };
+    result.map_err(bad)?;
+    audit.append(&session, Action::Finish).map_err(broken)?;
+    Ok(format!("Session {} finished", session))
+}
+
+#[get("/<session>/arms/<index>/swap/<slot>")]
+fn admin_swap(_admin: Admin, session: String, index: usize, slot: usize, game: State<Mutex<Game>>, rooms: State<Rooms>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    with_game(&session, &game, &rooms, |game| {
+        game.swap_slot(index, slot)
+    }).map_err(bad)?;
+    audit.append(&session, Action::Swap { arm: index, slot }).map_err(broken)?;
+    Ok(format!("Arm {} now uses slot {}", index, slot))
+}
+
+#[get("/<session>/arms/<index>/set?<kind>&<params>")]
+fn admin_set(_admin: Admin, session: String, index: usize, kind: String, params: Option<String>, game: State<Mutex<Game>>, rooms: State<Rooms>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let params = parse_params(&params.unwrap_or_default()).map_err(bad)?;
+    let slot = with_game(&session, &game, &rooms, |game| game.replace_slot(index, &kind, &params)).map_err(bad)?;
+    audit.append(&session, Action::Replace { arm: index, kind: kind.clone(), params }).map_err(broken)?;
+    Ok(format!("Arm {} now uses slot {} ({})", index, slot, kind))
+}
+
+fn audit_rounds(audit: &AuditLog, id: &str, room: &Room, before: usize) -> Result<(), String> {
+    for result in room.results_since(before) {
+        audit.append(&format!("room:{}", id), Action::Round { result: result.clone() })?;
+    }
+    Ok(())
+}
+
+#[get("/<id>/create?<arms>&<collision>&<capacity>&<rounds>&<timeout>")]
+fn create_room(_quota: NewGame, id: String, arms: Option<usize>, collision: Option<String>, capacity: Option<f64>, rounds: Option<usize>, timeout: Option<u64>, rooms: State<Rooms>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut rooms = rooms.lock().unwrap();
+    if rooms.contains_key(&id) {
+        return Err(Custom(Status::Conflict, format!("Room {} already exists", id)))
+    }
+    let collision = collision.map(|c| c.parse::<Collision>()).unwrap_or(Ok(Collision::Split)).map_err(bad)?;
+    rooms.insert(id.clone(), Room::new(Game::new(arms.unwrap_or(10)), collision, capacity, rounds, timeout.map(Duration::from_secs)));
+    audit.append(&format!("room:{}", id), Action::Open { kind: "room".to_string() }).map_err(broken)?;
+    Ok(format!("Room {} created", id))
+}
+
+#[get("/<id>/join/<player>")]
+fn join_room(id: String, player: String, rooms: State<Rooms>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut rooms = rooms.lock().unwrap();
+    let room = rooms.get_mut(&id).ok_or(format!("No room {}", id)).map_err(missing)?;
+    room.join(&player).map_err(bad)?;
+    audit.append(&format!("room:{}", id), Action::Join { player: player.clone() }).map_err(broken)?;
+    Ok(format!("{} joined room {}", player, id))
+}
+
+#[get("/<id>/play/<player>/<index>")]
+fn play_room(_quota: Pull, id: String, player: String, index: usize, rooms: State<Rooms>, metrics: State<Arc<Metrics>>, audit: State<Arc<AuditLog>>) -> Result<String, Custom<String>> {
+    let mut rooms = rooms.lock().unwrap();
+    let room = rooms.get_mut(&id).ok_or(format!("No room {}", id)).map_err(missing)?;
+    let before = room.round();
+    let result = room.submit(&player, index, Instant::now());
+    if result.is_ok() {
+        audit.append(&format!("room:{}", id), Action::Submit { player: player.clone(), arm: index }).map_err(broken)?;
+    }
+    audit_rounds(&audit, &id, room, before).map_err(broken)?;
+    let result = result.map_err(bad)?;
+    metrics.pull();
+    match result {
+        Some(result) => Ok(format!("Round {}: won {}", result.round, result.payouts[&player])),
+        None => Ok(format!("Waiting for {}", room.waiting_for().join(", "))),
+    }
+}
+
+#[get("/<id>/round")]
+fn round_room(id: String, rooms: State<Rooms>, audit: State<Arc<AuditLog>>) -> Result<Json<String>, Custom<Json<String>>> {
+    let mut rooms = rooms.lock().unwrap();
+    let room = rooms.get_mut(&id).ok_or(format!("No room {}", id)).map_err(missing_json)?;
+    let before = room.round();
+    let expired = room.expire(Instant::now());
+    audit_rounds(&audit, &id, room, before).map_err(|e| Custom(Status::InternalServerError, Json(serde_json::json!({ "error": e }).to_string())))?;
+    Ok(Json(serde_json::json!({
+        "round": room.round(),
+        "finished": room.is_finished(),
+        "waiting_for": room.waiting_for(),
+        "last": room.last_result(),
+        "error": expired.err(),
+    }).to_string()))
+}
+
+#[get("/<id>/scoreboard")]
+fn scoreboard_room(id: String, rooms: State<Rooms>) -> Result<Json<String>, Custom<Json<String>>> {
+    let rooms = rooms.lock().unwrap();
+    let room = rooms.get(&id).ok_or(format!("No room {}", id)).map_err(missing_json)?;
+    Ok(Json(serde_json::to_string(&room.scoreboard()).unwrap()))
+}
+
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name).ok().and_then(|x| x.parse().ok()).unwrap_or(default)
+}
 
 pub fn main() {
-    rocket::ignite().mount(
+    let metrics = Arc::new(Metrics::new());
+    let audit = match env::var("BANDIT_AUDIT_LOG") {
+        Ok(path) => match AuditLog::open(&path) {
+            Ok(audit) => audit,
+            Err(e) => {
+                eprintln!("Cannot open the audit log: {}", e);
+                process::exit(1)
+            }
+        },
+        Err(_) => AuditLog::disabled(),
+    };
+    let defaults = Limits::default();
+    let limits = Limits {
+        pulls_per_minute: env_or("BANDIT_PULLS_PER_MINUTE", defaults.pulls_per_minute),
+        games_per_day: env_or("BANDIT_GAMES_PER_DAY", defaults.games_per_day),
+        keys_per_day: env_or("BANDIT_KEYS_PER_DAY", defaults.keys_per_day),
+    };
+    let required = env::var("BANDIT_REQUIRE_KEYS").map(|v| v != "0" && v != "false").unwrap_or(true);
+    let keys = KeyStore::new(limits, required);
+    let setup = Setup::Seeded { n_arms: 10, reward_type: RewardType::Binary, seed: rand::random() };
+    let game = setup.build().unwrap();
+    let opened = audit.append("game", Action::Create { setup })
+        .and_then(|_| audit.append("continuous", Action::Open { kind: "continuous".to_string() }));
+    if let Err(e) = opened {
+        eprintln!("Cannot write to the audit log: {}", e);
+        process::exit(1)
+    }
+    rocket::ignite().mount("/", routes![ui, healthz, prometheus])
+    .mount(
         "/game",
         routes![
             desc, 
@@ -50,8 +568,58 @@ pub fn main() {
             play, 
             score,
             reset,
+            finish,
+            suggest,
+            status,
+            analyze,
+        ]
+    )
+    .mount(
+        "/continuous",
+        routes![
+            desc_continuous,
+            start_continuous,
+            play_continuous,
+            score_continuous,
+            finish_continuous,
+        ]
+    )
+    .mount(
+        "/keys",
+        routes![
+            register_key,
+            key_usage,
+        ]
+    )
+    .mount(
+        "/admin",
+        routes![
+            admin_sessions,
+            admin_profiles,
+            admin_finish,
+            admin_swap,
+            admin_set,
+        ]
+    )
+    .mount(
+        "/room",
+        routes![
+            create_room,
+            join_room,
+            play_room,
+            round_room,
+            scoreboard_room,
         ]
     )
-    .manage(Mutex::new(Game::new(10)))
+    .manage(Mutex::new(game))
+    .manage(Mutex::new(ContinuousGame::new(1)))
+    .manage(Rooms::default())
+    .manage(metrics.clone())
+    .manage(Arc::new(audit))
+    .manage(AdminToken(env::var("BANDIT_ADMIN_TOKEN").ok()))
+    .manage(keys)
+    .register(catchers![unauthorized, too_many_requests])
+    .attach(Timer(metrics))
+    .attach(Refunds)
     .launch();
 }
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::game::{EpisodeMode, Game, RewardType};
+use crate::registry::SlotParams;
+use crate::room::RoundResult;
+use crate::spec::GameSpec;
+
+
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Setup {
+    Seeded { n_arms: usize, reward_type: RewardType, seed: u64 },
+    Spec(GameSpec),
+}
+
+impl Setup {
+    pub fn build(&self) -> Result<Game, String> {
+        match self {
+            Setup::Seeded { n_arms, reward_type, seed } => Ok(Game::seeded(*n_arms, *reward_type, *seed)),
+            Setup::Spec(spec) if spec.seed.is_none() => Err("an audited spec needs a seed".to_string()),
+            Setup::Spec(spec) => Game::from_spec(spec),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Action {
+    Create { setup: Setup },
+    Open { kind: String },
+    Start { n_games: Option<usize>, mode: EpisodeMode },
+    Play { arm: usize, reward: f64 },
+    Reset,
+    Finish,
+    Replace { arm: usize, kind: String, params: SlotParams },
+    Swap { arm: usize, slot: usize },
+    Begin { horizon: Option<usize> },
+    Probe { x: Vec<f64>, reward: f64 },
+    Join { player: String },
+    Submit { player: String, arm: usize },
+    Round { result: RoundResult },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub seq: u64,
+    pub time: u64,
+    pub session: String,
+    pub action: Action,
+    pub prev: String,
+}
+
+impl Record {
+    pub fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_string(self).unwrap().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(flatten)]
+    pub record: Record,
+    pub hash: String,
+}
+
+
+struct Chain {
+    file: File,
+    seq: u64,
+    prev: String,
+}
+
+pub struct AuditLog {
+    chain: Mutex<Option<Chain>>,
+}
+
+impl AuditLog {
+    pub fn disabled() -> AuditLog {
+        AuditLog { chain: Mutex::new(None) }
+    }
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<AuditLog, String> {
+        let path = path.as_ref();
+        let (seq, prev) = match fs::read_to_string(path) {
+            Ok(content) => match content.lines().rfind(|l| !l.trim().is_empty()) {
+                Some(line) => {
+                    let last: Entry = serde_json::from_str(line).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    (last.record.seq + 1, last.hash)
+                }
+                None => (0, GENESIS.to_string()),
+            },
+            Err(_) => (0, GENESIS.to_string()),
+        };
+        let file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(AuditLog { chain: Mutex::new(Some(Chain { file, seq, prev })) })
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.chain.lock().unwrap().is_some()
+    }
+    pub fn append(&self, session: &str, action: Action) -> Result<(), String> {
+        let mut chain = self.chain.lock().unwrap();
+        let chain = match chain.as_mut() {
+            Some(chain) => chain,
+            None => return Ok(())
+        };
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+        let record = Record { seq: chain.seq, time, session: session.to_string(), action, prev: chain.prev.clone() };
+        let entry = Entry { hash: record.digest(), record };
+        let mut line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+        chain.file.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        chain.seq += 1;
+        chain.prev = entry.hash;
+        Ok(())
+    }
+}
+
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Verified {
+    pub entries: u64,
+    pub sessions: usize,
+    pub plays: u64,
+    pub unreplayed: u64,
+}
+
+// Sessions created with Create are replayed and every payout is checked.
+// Rooms and the continuous game are opened with Open instead: their entries
+// are chained and hashed like the rest, but their rewards are not replayed.
+pub fn verify(content: &str) -> Result<Verified, String> {
+    let mut games: HashMap<String, Game> = HashMap::new();
+    let mut opened: HashSet<String> = HashSet::new();
+    let mut verified = Verified::default();
+    let mut prev = GENESIS.to_string();
+    for (i, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let at = |e: String| format!("line {}: {}", i + 1, e);
+        let entry: Entry = serde_json::from_str(line).map_err(|e| at(e.to_string()))?;
+        let record = entry.record;
+        if record.seq != verified.entries {
+            return Err(at(format!("expected seq {}, found {}", verified.entries, record.seq)))
+        }
+        if record.prev != prev {
+            return Err(at("the chain is broken: prev does not match the previous hash".to_string()))
+        }
+        if record.digest() != entry.hash {
+            return Err(at("the entry does not match its hash".to_string()))
+        }
+        if let Action::Create { setup } = &record.action {
+            games.insert(record.session.clone(), setup.build().map_err(at)?);
+            opened.remove(&record.session);
+            verified.sessions += 1;
+        } else if let Action::Open { .. } = &record.action {
+            games.remove(&record.session);
+            opened.insert(record.session.clone());
+            verified.sessions += 1;
+        } else if opened.contains(&record.session) {
+            verified.unreplayed += 1;
+        } else {
+            let game = games.get_mut(&record.session).ok_or(at(format!("session {} was never created", record.session)))?;
+            match &record.action {
+                Action::Start { n_games, mode } => {
+                    game.set_mode(*mode);
+                    game.start(*n_games);
+                }
+                Action::Play { arm, reward } => {
+                    let replayed = game.play(*arm).map_err(at)?;
+                    if replayed.to_bits() != reward.to_bits() {
+                        return Err(at(format!("arm {} paid {} but the log says {}", arm, replayed, reward)))
+                    }
+                    verified.plays += 1;
+                }
+                Action::Reset => game.reset(),
+                Action::Finish => game.finish(),
+                Action::Replace { arm, kind, params } => game.replace_slot(*arm, kind, params).map(|_| ()).map_err(at)?,
+                Action::Swap { arm, slot } => game.swap_slot(*arm, *slot).map_err(at)?,
+                Action::Create { .. } | Action::Open { .. } => unreachable!(),
+                action => return Err(at(format!("session {} is a replayed game and cannot log {:?}", record.session, action))),
+            }
+        }
+        prev = entry.hash;
+        verified.entries += 1;
+    }
+    Ok(verified)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+    use super::*;
+
+    fn write_log(name: &str, plays: &[usize], forged: Option<f64>) -> String {
+        let path = env::temp_dir().join(format!("bandit-audit-{}-{}.jsonl", name, process::id()));
+        let _ = fs::remove_file(&path);
+        let log = AuditLog::open(&path).unwrap();
+        let setup = Setup::Seeded { n_arms: 3, reward_type: RewardType::Gaussian, seed: 11 };
+        let mut game = setup.build().unwrap();
+        log.append("game", Action::Create { setup }).unwrap();
+        game.start(None);
+        log.append("game", Action::Start { n_games: None, mode: game.mode() }).unwrap();
+        for arm in plays {
+            let reward = game.play(*arm).unwrap();
+            log.append("game", Action::Play { arm: *arm, reward: forged.unwrap_or(reward) }).unwrap();
+        }
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        content
+    }
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn a_clean_chain_verifies() {
+        let verified = verify(&write_log("clean", &[0, 1, 2, 1], None)).unwrap();
+        assert_eq!(verified, Verified { entries: 6, sessions: 1, plays: 4, unreplayed: 0 });
+    }
+
+    #[test]
+    fn an_edited_reward_fails_at_its_line() {
+        let mut lines = lines(&write_log("edited", &[0, 1, 2], None));
+        let mut entry: Entry = serde_json::from_str(&lines[3]).unwrap();
+        entry.record.action = Action::Play { arm: 1, reward: 100. };
+        lines[3] = serde_json::to_string(&entry).unwrap();
+        assert!(verify(&lines.join("\n")).unwrap_err().starts_with("line 4: the entry does not match its hash"));
+    }
+
+    #[test]
+    fn a_deleted_entry_fails_at_the_gap() {
+        let mut lines = lines(&write_log("deleted", &[0, 1, 2], None));
+        lines.remove(2);
+        assert!(verify(&lines.join("\n")).unwrap_err().starts_with("line 3: expected seq 2, found 3"));
+    }
+
+    #[test]
+    fn reordered_entries_fail_at_the_first_swap() {
+        let mut lines = lines(&write_log("reordered", &[0, 1, 2], None));
+        lines.swap(3, 4);
+        assert!(verify(&lines.join("\n")).unwrap_err().starts_with("line 4: expected seq 3, found 4"));
+    }
+
+    #[test]
+    fn a_rehashed_forgery_fails_the_replay() {
+        let err = verify(&write_log("forged", &[2], Some(5.))).unwrap_err();
+        assert!(err.starts_with("line 3: arm 2 paid"), "{}", err);
+        assert!(err.ends_with("but the log says 5"), "{}", err);
+    }
+
+    #[test]
+    fn reopening_continues_the_chain() {
+        let path = env::temp_dir().join(format!("bandit-audit-reopen-{}.jsonl", process::id()));
+        let _ = fs::remove_file(&path);
+        let setup = Setup::Seeded { n_arms: 2, reward_type: RewardType::Binary, seed: 5 };
+        AuditLog::open(&path).unwrap().append("game", Action::Create { setup }).unwrap();
+        AuditLog::open(&path).unwrap().append("game", Action::Reset).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(verify(&content).unwrap().entries, 2);
+    }
+}
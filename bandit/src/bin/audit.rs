@@ -0,0 +1,18 @@
+use std::env;
+use std::fs;
+use bandit::audit;
+
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.iter().map(|x| x.as_str()).collect::<Vec<&str>>().as_slice() {
+        ["verify", path] => {
+            let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            let verified = audit::verify(&content)?;
+            println!("OK: {} entries, {} sessions, {} plays replayed, {} entries chained but not replayed",
+                verified.entries, verified.sessions, verified.plays, verified.unreplayed);
+            Ok(())
+        }
+        _ => Err("Usage: audit verify <log.jsonl>".to_string())
+    }
+}
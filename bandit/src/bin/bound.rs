@@ -0,0 +1,43 @@
+use std::env;
+use bandit::bounds::{self, ArmModel, Family};
+use bandit::spec::GameSpec;
+
+
+fn parse(args: &[String]) -> Result<(Vec<ArmModel>, Vec<usize>), String> {
+    let mut family = Family::Bernoulli;
+    let mut profiles: Option<Vec<f64>> = None;
+    let mut spec = None;
+    let mut horizons = vec![100, 1000, 10000, 100000];
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or(format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--spec" => spec = Some(GameSpec::load(value)?),
+            "--profiles" => profiles = Some(value.split(',').map(|x| x.trim().parse().map_err(|_| format!("Invalid profile: {}", x))).collect::<Result<_, String>>()?),
+            "--family" => family = value.parse()?,
+            "--horizons" => horizons = value.split(',').map(|x| x.trim().parse().map_err(|_| format!("Invalid horizon: {}", x))).collect::<Result<_, String>>()?,
+            _ => return Err(format!("Unknown option: {}", flag))
+        }
+    }
+    let arms = match (spec, profiles) {
+        (Some(spec), None) => bounds::from_spec(&spec)?,
+        (None, Some(profiles)) => bounds::from_profiles(&profiles, family),
+        _ => return Err("Pass exactly one of --spec or --profiles".to_string())
+    };
+    Ok((arms, horizons))
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (arms, horizons) = parse(&args)?;
+    let bound = bounds::lai_robbins(&arms);
+    println!("{:>4} {:>10} {:>10} {:>10} {:>12}", "arm", "mean", "gap", "kl", "pulls/ln T");
+    for arm in bound.arms.iter() {
+        println!("{:>4} {:>10.4} {:>10.4} {:>10.4} {:>12.2}", arm.arm, arm.mean, arm.gap, arm.kl, arm.pulls);
+    }
+    println!("regret >= {:.3} ln T asymptotically", bound.constant);
+    for t in horizons {
+        println!("T = {:<8} asymptotic rate {:.2}", t, bound.at(t));
+    }
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::env;
+use std::io::{self, BufRead, Read, Write};
+use std::net::TcpStream;
+use bandit::game::Game;
+use bandit::spec::GameSpec;
+
+
+trait Backend {
+    fn start(&mut self, n_games: Option<usize>) -> Result<(), String>;
+    fn pull(&mut self, index: usize) -> Result<f64, String>;
+    fn reset(&mut self) -> Result<(), String>;
+    fn reveal(&mut self) -> Result<Vec<f64>, String>;
+}
+
+struct Local {
+    game: Game,
+    horizon: Option<usize>,
+}
+
+impl Backend for Local {
+    fn start(&mut self, n_games: Option<usize>) -> Result<(), String> {
+        self.game.start(n_games.or(self.horizon));
+        Ok(())
+    }
+    fn pull(&mut self, index: usize) -> Result<f64, String> {
+        self.game.play(index)
+    }
+    fn reset(&mut self) -> Result<(), String> {
+        self.game.reset();
+        Ok(())
+    }
+    fn reveal(&mut self) -> Result<Vec<f64>, String> {
+        Ok(self.game.profiles())
+    }
+}
+
+struct Remote {
+    addr: String,
+    key: Option<String>,
+}
+
+impl Remote {
+    fn get(&self, path: &str) -> Result<String, String> {
+        let mut stream = TcpStream::connect(&self.addr).map_err(|e| e.to_string())?;
+        let key = self.key.as_ref().map(|k| format!("X-Api-Key: {}\r\n", k)).unwrap_or_default();
+        write!(stream, "GET /game{} HTTP/1.1\r\nHost: {}\r\n{}Connection: close\r\n\r\n", path, self.addr, key)
+            .map_err(|e| e.to_string())?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).map_err(|e| e.to_string())?;
+        let mut parts = response.splitn(2, "\r\n\r\n");
+        let head = parts.next().unwrap_or("");
+        let body = parts.next().unwrap_or("").to_string();
+        match head.lines().next().and_then(|l| l.split_whitespace().nth(1)) {
+            Some("200") => Ok(body),
+            Some(code) => Err(format!("HTTP {}: {}", code, body.trim())),
+            None => Err("Malformed response".to_string())
+        }
+    }
+    fn n_arms(&self) -> Result<usize, String> {
+        let state: serde_json::Value = serde_json::from_str(&self.get("/state")?).map_err(|e| e.to_string())?;
+        state["n_arms"].as_u64().map(|n| n as usize).ok_or("The server did not report n_arms.".to_string())
+    }
+}
+
+impl Backend for Remote {
+    fn start(&mut self, n_games: Option<usize>) -> Result<(), String> {
+        let path = match n_games {
+            Some(n) => format!("/start?n_games={}", n),
+            None => "/start".to_string(),
+        };
+        self.get(&path).map(|_| ())
+    }
+    fn pull(&mut self, index: usize) -> Result<f64, String> {
+        let body = self.get(&format!("/play/{}", index))?;
+        match body.trim().strip_prefix("Won ") {
+            Some(val) => val.parse::<f64>().map_err(|e| e.to_string()),
+            None => Err(body)
+        }
+    }
+    fn reset(&mut self) -> Result<(), String> {
+        self.get("/reset").map(|_| ())
+    }
+    fn reveal(&mut self) -> Result<Vec<f64>, String> {
+        Err("The server does not reveal its machines.".to_string())
+    }
+}
+
+
+struct Session {
+    backend: Box<dyn Backend>,
+    counts: Vec<usize>,
+    sums: Vec<f64>,
+}
+
+impl Session {
+    fn new(backend: Box<dyn Backend>, n_arms: usize) -> Session {
+        Session { backend, counts: vec![0; n_arms], sums: vec![0.; n_arms] }
+    }
+    fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|x| *x = 0);
+        self.sums.iter_mut().for_each(|x| *x = 0.);
+    }
+    fn table(&self, profiles: Option<&[f64]>) -> String {
+        let mut out = String::from(" arm  pulls      total       mean");
+        if profiles.is_some() {
+            out.push_str("       true");
+        }
+        for (i, (n, s)) in self.counts.iter().zip(self.sums.iter()).enumerate() {
+            let mean = if *n == 0 { 0. } else { s / *n as f64 };
+            out.push_str(&format!("\n{:>4} {:>6} {:>10.2} {:>10.4}", i, n, s, mean));
+            if let Some(p) = profiles.and_then(|p| p.get(i)) {
+                out.push_str(&format!(" {:>10.4}", p));
+            }
+        }
+        let total: usize = self.counts.iter().sum();
+        let score: f64 = self.sums.iter().sum();
+        out.push_str(&format!("\nscore {} over {} pulls", score, total));
+        out
+    }
+    fn exec(&mut self, line: &str) -> Result<Option<String>, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => Ok(Some(String::new())),
+            ["start"] => self.backend.start(None).map(|_| Some("Game start".to_string())),
+            ["start", n] => {
+                let n = n.parse::<usize>().map_err(|e| e.to_string())?;
+                self.backend.start(Some(n)).map(|_| Some("Game start".to_string()))
+            }
+            ["pull", i] | ["p", i] => {
+                let index = i.parse::<usize>().map_err(|e| e.to_string())?;
+                let reward = self.backend.pull(index)?;
+                if let (Some(n), Some(s)) = (self.counts.get_mut(index), self.sums.get_mut(index)) {
+                    *n += 1;
+                    *s += reward;
+                }
+                Ok(Some(format!("Won {}\n{}", reward, self.table(None))))
+            }
+            ["stats"] => Ok(Some(self.table(None))),
+            ["reset"] => {
+                self.backend.reset()?;
+                self.clear();
+                Ok(Some("Game reset".to_string()))
+            }
+            ["reveal"] => {
+                let profiles = self.backend.reveal()?;
+                Ok(Some(self.table(Some(&profiles))))
+            }
+            ["help"] => Ok(Some(HELP.to_string())),
+            ["quit"] | ["exit"] => Ok(None),
+            _ => Err(format!("Unknown command: {}", line.trim())),
+        }
+    }
+}
+
+const HELP: &str = "commands: start [n] | pull <arm> | stats | reset | reveal | help | quit";
+
+
+fn parse_arms(args: &[String]) -> usize {
+    args.first().and_then(|x| x.parse::<usize>().ok()).unwrap_or(10)
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (backend, n_arms): (Box<dyn Backend>, usize) = match args.as_slice() {
+        [flag, addr, ..] if flag == "--remote" => {
+            let remote = Remote { addr: addr.clone(), key: env::var("BANDIT_API_KEY").ok() };
+            let n_arms = remote.n_arms()?;
+            (Box::new(remote), n_arms)
+        }
+        [flag, path, ..] if flag == "--spec" => {
+            let spec = GameSpec::load(path)?;
+            let game = Game::from_spec(&spec)?;
+            (Box::new(Local { game, horizon: spec.horizon }), spec.arms.len())
+        }
+        rest => {
+            let n_arms = parse_arms(rest);
+            (Box::new(Local { game: Game::new(n_arms), horizon: None }), n_arms)
+        }
+    };
+    let mut session = Session::new(backend, n_arms);
+    println!("{}", HELP);
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break
+        }
+        match session.exec(&line) {
+            Ok(Some(out)) => println!("{}", out),
+            Ok(None) => break,
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::env;
+use bandit::game::{EpisodeMode, Game, RewardType};
+use bandit::markov::{self, GittinsPolicy, IndexPolicy, MyopicPolicy};
+use bandit::spec::GameSpec;
+
+
+struct Options {
+    spec: Option<GameSpec>,
+    arms: usize,
+    seed: u64,
+    horizon: Option<usize>,
+    discount: f64,
+    repetitions: usize,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options { spec: None, arms: 3, seed: 0, horizon: None, discount: 0.95, repetitions: 100 };
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or(format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--spec" => options.spec = Some(GameSpec::load(value)?),
+            "--arms" => options.arms = value.parse().map_err(|_| format!("Invalid --arms: {}", value))?,
+            "--seed" => options.seed = value.parse().map_err(|_| format!("Invalid --seed: {}", value))?,
+            "--horizon" => options.horizon = Some(value.parse().map_err(|_| format!("Invalid --horizon: {}", value))?),
+            "--discount" => options.discount = value.parse().map_err(|_| format!("Invalid --discount: {}", value))?,
+            "--reps" => options.repetitions = value.parse().map_err(|_| format!("Invalid --reps: {}", value))?,
+            _ => return Err(format!("Unknown option: {}", flag))
+        }
+    }
+    if !(options.discount > 0. && options.discount < 1.) {
+        return Err(format!("--discount must be in (0, 1), got {}", options.discount))
+    }
+    if options.repetitions == 0 || options.arms == 0 {
+        return Err("--reps and --arms must be at least 1".to_string())
+    }
+    Ok(options)
+}
+
+fn build(options: &Options) -> Result<Game, String> {
+    let mut game = match &options.spec {
+        Some(spec) => Game::from_spec(spec)?,
+        None => Game::seeded(options.arms, RewardType::Markov, options.seed),
+    };
+    game.set_mode(EpisodeMode::Fresh);
+    Ok(game)
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse(&args)?;
+    let game = build(&options)?;
+    let arms = game.markov_arms().ok_or("Every arm must be a markov arm.".to_string())?;
+    let horizon = options.horizon.or(game.horizon()).unwrap_or(200);
+    let gittins = GittinsPolicy::new(&arms, options.discount);
+    for (i, indices) in gittins.indices().iter().enumerate() {
+        let indices: Vec<String> = indices.iter().map(|x| format!("{:.4}", x)).collect();
+        println!("arm {:<3} gittins indices [{}]", i, indices.join(", "));
+    }
+    let policies: Vec<Box<dyn IndexPolicy>> = vec![Box::new(gittins), Box::new(MyopicPolicy::new(&arms))];
+    for policy in policies.iter() {
+        let mut game = build(&options)?;
+        let mut total = 0.;
+        for _ in 0..options.repetitions {
+            total += markov::run(policy.as_ref(), &mut game, horizon, options.discount)?;
+        }
+        println!("{:<8} mean discounted reward {:.4} over {} episodes of {} rounds",
+            policy.name(), total / options.repetitions as f64, options.repetitions, horizon);
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use bandit::env::{ContextualGame, Environment};
+use bandit::game::{Game, RewardType};
+use bandit::spec::GameSpec;
+
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+enum Command {
+    Describe,
+    Reset,
+    Step { action: usize },
+    Close,
+}
+
+#[derive(Debug, Serialize)]
+struct Describe {
+    n_actions: usize,
+    context_dim: usize,
+    horizon: Option<usize>,
+}
+
+fn build(args: &[String]) -> Result<Box<dyn Environment>, String> {
+    let mut arms = 10;
+    let mut horizon = Some(1000);
+    let mut seed = 0;
+    let mut reward_type = RewardType::Binary;
+    let mut contextual = None;
+    let mut spec = None;
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or(format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--arms" => arms = value.parse().map_err(|_| format!("Invalid --arms: {}", value))?,
+            "--horizon" => horizon = Some(value.parse().map_err(|_| format!("Invalid --horizon: {}", value))?),
+            "--seed" => seed = value.parse().map_err(|_| format!("Invalid --seed: {}", value))?,
+            "--rewards" => reward_type = value.parse()?,
+            "--contextual" => contextual = Some(value.parse::<usize>().map_err(|_| format!("Invalid --contextual: {}", value))?),
+            "--spec" => spec = Some(GameSpec::load(value)?),
+            _ => return Err(format!("Unknown option: {}", flag))
+        }
+    }
+    match (spec, contextual) {
+        (Some(spec), _) => Ok(Box::new(Game::from_spec(&spec)?)),
+        (None, Some(dim)) => Ok(Box::new(ContextualGame::seeded(arms, dim, horizon, seed))),
+        (None, None) => {
+            let mut game = Game::seeded(arms, reward_type, seed);
+            game.start(horizon);
+            Ok(Box::new(game))
+        }
+    }
+}
+
+fn handle(env: &mut dyn Environment, line: &str) -> Result<Option<serde_json::Value>, String> {
+    let command: Command = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    match command {
+        Command::Describe => {
+            let describe = Describe { n_actions: env.n_actions(), context_dim: env.context_dim(), horizon: env.horizon() };
+            Ok(Some(json!(describe)))
+        }
+        Command::Reset => Ok(Some(json!({ "observation": env.reset() }))),
+        Command::Step { action } => {
+            let (observation, reward, done, info) = env.step(action)?;
+            Ok(Some(json!({ "observation": observation, "reward": reward, "done": done, "info": info })))
+        }
+        Command::Close => Ok(None),
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut env = build(&args)?;
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue
+        }
+        let response = match handle(env.as_mut(), &line) {
+            Ok(Some(value)) => value,
+            Ok(None) => break,
+            Err(e) => json!({ "error": e }),
+        };
+        writeln!(out, "{}", response).map_err(|e| e.to_string())?;
+        out.flush().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
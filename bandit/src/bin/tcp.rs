@@ -0,0 +1,44 @@
+use std::env;
+use std::sync::Arc;
+use rand::random;
+use bandit::audit::{AuditLog, Setup};
+use bandit::game::{Game, RewardType};
+use bandit::spec::GameSpec;
+use bandit::tcp;
+
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut addr = "127.0.0.1:7878".to_string();
+    let mut arms = 10;
+    let mut seed = 0;
+    let mut reward_type = RewardType::Binary;
+    let mut spec = None;
+    let mut audit = AuditLog::disabled();
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or(format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--addr" => addr = value.clone(),
+            "--arms" => arms = value.parse().map_err(|_| format!("Invalid --arms: {}", value))?,
+            "--seed" => seed = value.parse().map_err(|_| format!("Invalid --seed: {}", value))?,
+            "--rewards" => reward_type = value.parse()?,
+            "--spec" => spec = Some(GameSpec::load(value)?),
+            "--audit" => audit = AuditLog::open(value)?,
+            _ => return Err(format!("Unknown option: {}", flag))
+        }
+    }
+    let factory: Arc<dyn Fn(usize) -> Setup + Send + Sync> = match spec {
+        Some(spec) => {
+            Game::from_spec(&spec)?;
+            Arc::new(move |_| {
+                let mut spec = spec.clone();
+                spec.seed.get_or_insert_with(random);
+                Setup::Spec(spec)
+            })
+        }
+        None => Arc::new(move |_| Setup::Seeded { n_arms: arms, reward_type, seed }),
+    };
+    eprintln!("listening on {}", addr);
+    tcp::serve(addr.as_str(), factory, Arc::new(audit)).map_err(|e| e.to_string())
+}
@@ -0,0 +1,95 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use bandit::feedback;
+use bandit::game::RewardType;
+use bandit::plot;
+use bandit::rng;
+use bandit::simulation::{self, Report, Runner, Tournament};
+
+
+fn list<T: FromStr>(value: &str) -> Result<Vec<T>, String>
+    where
+        T::Err: ToString
+{
+    value.split(',').map(|x| x.trim().parse::<T>().map_err(|e| format!("{}: {}", x, e.to_string()))).collect()
+}
+
+fn parse(args: &[String]) -> Result<(Tournament, PathBuf), String> {
+    let mut agents = "ucb1,thompson,egreedy:0.1".to_string();
+    let mut arms = vec![10];
+    let mut horizons = vec![1000];
+    let mut reward_types = vec![RewardType::Binary];
+    let mut repetitions = 20;
+    let mut seed = 0;
+    let mut failure_threshold = 0.5;
+    let mut graph = "bandit".to_string();
+    let mut edge_prob = 0.5;
+    let mut runner = Runner::new();
+    let mut out = PathBuf::from("tournament");
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or(format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--agents" => agents = value.clone(),
+            "--arms" => arms = list(value)?,
+            "--horizons" => horizons = list(value)?,
+            "--rewards" => reward_types = list(value)?,
+            "--reps" => repetitions = value.parse().map_err(|_| format!("Invalid --reps: {}", value))?,
+            "--seed" => seed = value.parse().map_err(|_| format!("Invalid --seed: {}", value))?,
+            "--failure" => failure_threshold = value.parse().map_err(|_| format!("Invalid --failure: {}", value))?,
+            "--threads" => runner.threads = value.parse().map_err(|_| format!("Invalid --threads: {}", value))?,
+            "--rng" => runner.source = rng::source_by_name(value)?,
+            "--objective" => runner.objective = value.parse()?,
+            "--stride" => runner.stride = value.parse().map_err(|_| format!("Invalid --stride: {}", value))?,
+            "--graph" => graph = value.clone(),
+            "--edge-prob" => edge_prob = value.parse().map_err(|_| format!("Invalid --edge-prob: {}", value))?,
+            "--out" => out = PathBuf::from(value),
+            _ => return Err(format!("Unknown option: {}", flag))
+        }
+    }
+    runner.graph = feedback::graph_by_name(&graph, edge_prob)?;
+    if repetitions == 0 {
+        return Err("--reps must be at least 1".to_string())
+    }
+    if horizons.contains(&0) || arms.contains(&0) {
+        return Err("--horizons and --arms must be at least 1".to_string())
+    }
+    let agents = agents.split(',').map(|x| x.trim().to_string()).collect();
+    let mut tournament = Tournament::grid(agents, &arms, &horizons, &reward_types);
+    tournament.repetitions = repetitions;
+    tournament.seed = seed;
+    tournament.failure_threshold = failure_threshold;
+    tournament.runner = runner;
+    Ok((tournament, out))
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (tournament, out) = parse(&args)?;
+    let (curves, reports) = tournament.run()?;
+    fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+    fs::write(out.join("regret.csv"), simulation::to_csv(&curves)).map_err(|e| e.to_string())?;
+    fs::write(out.join("regret.json"), simulation::to_json(&curves)?).map_err(|e| e.to_string())?;
+    let total = Report {
+        runs: vec![],
+        pulls: reports.iter().map(|r| r.pulls).sum(),
+        elapsed: reports.iter().map(|r| r.elapsed).sum(),
+    };
+    for config in tournament.configs.iter() {
+        let group: Vec<_> = curves.iter().filter(|c| c.config == *config).collect();
+        let svg = plot::regret_svg(&config.label(), &group);
+        fs::write(out.join(format!("{}.svg", config.label())), svg).map_err(|e| e.to_string())?;
+        for c in group {
+            let bound = c.lower_bound.as_ref().map(|b| format!(", asymptotic (Lai-Robbins) rate {:.2}", b[b.len() - 1])).unwrap_or_default();
+            println!("{:<16} {:<24} final regret {:.2} ± {:.2}{}, failure rate {:.0}%, {} {:.4}",
+                c.agent, config.label(), c.mean[c.mean.len() - 1], c.upper[c.upper.len() - 1] - c.mean[c.mean.len() - 1],
+                bound, 100. * c.failure_rate, tournament.runner.objective, c.score);
+        }
+    }
+    println!("{} pulls in {:.2}s on {} threads with {} rng and {} feedback ({:.0} pulls/sec)",
+        total.pulls, total.elapsed.as_secs_f64(), tournament.runner.threads, tournament.runner.source.name(), tournament.runner.graph, total.throughput());
+    println!("results written to {}", out.display());
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::env;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use bandit::continuous::{self, ContinuousGame, Domain};
+
+
+struct Options {
+    agents: Vec<String>,
+    dim: usize,
+    peaks: usize,
+    lipschitz: f64,
+    horizon: usize,
+    repetitions: usize,
+    seed: u64,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        agents: vec!["zooming".to_string(), "hoo".to_string(), "gp-ucb".to_string()],
+        dim: 1, peaks: 3, lipschitz: 1., horizon: 2000, repetitions: 10, seed: 0,
+    };
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or(format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--agents" => options.agents = value.split(',').map(|x| x.trim().to_string()).collect(),
+            "--dim" => options.dim = value.parse().map_err(|_| format!("Invalid --dim: {}", value))?,
+            "--peaks" => options.peaks = value.parse().map_err(|_| format!("Invalid --peaks: {}", value))?,
+            "--lipschitz" => options.lipschitz = value.parse().map_err(|_| format!("Invalid --lipschitz: {}", value))?,
+            "--horizon" => options.horizon = value.parse().map_err(|_| format!("Invalid --horizon: {}", value))?,
+            "--reps" => options.repetitions = value.parse().map_err(|_| format!("Invalid --reps: {}", value))?,
+            "--seed" => options.seed = value.parse().map_err(|_| format!("Invalid --seed: {}", value))?,
+            _ => return Err(format!("Unknown option: {}", flag))
+        }
+    }
+    if options.dim == 0 || options.horizon == 0 || options.repetitions == 0 {
+        return Err("--dim, --horizon and --reps must be at least 1".to_string())
+    }
+    if !(options.lipschitz > 0. && options.lipschitz.is_finite()) {
+        return Err(format!("--lipschitz must be positive, got {}", options.lipschitz))
+    }
+    Ok(options)
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse(&args)?;
+    let domain = Domain::unit(options.dim);
+    for name in options.agents.iter() {
+        let mut agent = continuous::by_name(name, &domain, options.horizon)?;
+        let mut regrets = vec![];
+        for rep in 0..options.repetitions {
+            let seed = options.seed.wrapping_add(rep as u64);
+            let mut game = ContinuousGame::seeded(domain.clone(), options.lipschitz, options.peaks, seed);
+            let mut rng = StdRng::seed_from_u64(seed.rotate_left(32));
+            let curve = continuous::run(agent.as_mut(), &mut game, options.horizon, options.horizon, &mut rng)?;
+            regrets.push(curve[curve.len() - 1]);
+        }
+        let mean = regrets.iter().sum::<f64>() / regrets.len() as f64;
+        println!("{:<16} mean regret {:.2} after {} rounds over {} games", agent.name(), mean, options.horizon, options.repetitions);
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::str::FromStr;
+use std::vec::Vec;
+use serde::Serialize;
+use crate::game::{RewardType, GAUSSIAN_STD};
+use crate::registry::{param, param_or};
+use crate::spec::GameSpec;
+
+
+pub fn kl_bernoulli(p: f64, q: f64) -> f64 {
+    if !(0. ..=1.).contains(&q) {
+        return f64::INFINITY
+    }
+    let term = |a: f64, b: f64| if a <= 0. { 0. } else if b <= 0. { f64::INFINITY } else { a * (a / b).ln() };
+    term(p, q) + term(1. - p, 1. - q)
+}
+
+pub fn kl_gaussian(p: f64, q: f64, std: f64) -> f64 {
+    (p - q).powi(2) / (2. * std * std)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Family {
+    Bernoulli,
+    Gaussian { std: f64 },
+}
+
+impl Family {
+    pub fn for_reward_type(reward_type: RewardType) -> Result<Family, String> {
+        match reward_type {
+            RewardType::Binary => Ok(Family::Bernoulli),
+            RewardType::Gaussian => Ok(Family::Gaussian { std: GAUSSIAN_STD }),
+            _ => Err(format!("no Lai-Robbins bound for {} rewards", reward_type))
+        }
+    }
+    pub fn kl(&self, p: f64, q: f64) -> f64 {
+        match self {
+            Family::Bernoulli => kl_bernoulli(p, q),
+            Family::Gaussian { std } => kl_gaussian(p, q, *std),
+        }
+    }
+}
+
+impl FromStr for Family {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Family, String> {
+        let mut parts = s.splitn(2, ':');
+        match (parts.next().unwrap_or(""), parts.next()) {
+            ("bernoulli", None) | ("binary", None) => Ok(Family::Bernoulli),
+            ("gaussian", std) | ("normal", std) => {
+                let std = std.map(|x| x.parse::<f64>().map_err(|_| format!("Invalid std: {}", x))).transpose()?.unwrap_or(GAUSSIAN_STD);
+                if !(std > 0. && std.is_finite()) {
+                    return Err(format!("std must be positive, got {}", std))
+                }
+                Ok(Family::Gaussian { std })
+            }
+            _ => Err(format!("Unknown family: {}", s))
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArmModel {
+    pub family: Family,
+    pub location: f64,
+    pub cost: f64,
+}
+
+impl ArmModel {
+    pub fn new(family: Family, location: f64) -> ArmModel {
+        ArmModel { family, location, cost: 0. }
+    }
+    pub fn mean(&self) -> f64 {
+        self.location - self.cost
+    }
+    pub fn kl_to(&self, target: f64) -> f64 {
+        self.family.kl(self.location, target + self.cost)
+    }
+}
+
+pub fn from_profiles(profiles: &[f64], family: Family) -> Vec<ArmModel> {
+    profiles.iter().map(|mean| ArmModel::new(family, *mean)).collect()
+}
+
+pub fn from_spec(spec: &GameSpec) -> Result<Vec<ArmModel>, String> {
+    spec.arms.iter().enumerate().map(|(i, arm)| {
+        let label = arm.label(i);
+        if !arm.drift.is_empty() {
+            return Err(format!("{}: drifting arms are non-stationary", label))
+        }
+        let (family, location) = match arm.kind.as_str() {
+            "bernoulli" | "binary" => (Family::Bernoulli, param(&arm.params, "p")),
+            "gaussian" | "normal" => (Family::Gaussian { std: param_or(&arm.params, "std", GAUSSIAN_STD) }, param(&arm.params, "mean")),
+            kind => return Err(format!("{}: no Lai-Robbins bound for `{}` arms", label, kind))
+        };
+        let location = location.map_err(|e| format!("{}: {}", label, e))?;
+        Ok(ArmModel { family, location, cost: arm.cost })
+    }).collect()
+}
+
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArmBound {
+    pub arm: usize,
+    pub mean: f64,
+    pub gap: f64,
+    pub kl: f64,
+    pub pulls: f64,
+    pub regret: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LowerBound {
+    pub constant: f64,
+    pub arms: Vec<ArmBound>,
+}
+
+impl LowerBound {
+    pub fn at(&self, round: usize) -> f64 {
+        self.constant * (round.max(1) as f64).ln()
+    }
+    pub fn curve(&self, rounds: &[usize]) -> Vec<f64> {
+        rounds.iter().map(|t| self.at(*t)).collect()
+    }
+}
+
+pub fn lai_robbins(arms: &[ArmModel]) -> LowerBound {
+    let best = arms.iter().map(|a| a.mean()).fold(f64::NEG_INFINITY, f64::max);
+    let arms: Vec<ArmBound> = arms.iter().enumerate().map(|(i, a)| {
+        let gap = best - a.mean();
+        let kl = if gap > 1e-12 { a.kl_to(best) } else { 0. };
+        let pulls = if gap > 1e-12 && kl.is_finite() { 1. / kl } else { 0. };
+        ArmBound { arm: i, mean: a.mean(), gap, kl, pulls, regret: gap * pulls }
+    }).collect();
+    LowerBound { constant: arms.iter().map(|a| a.regret).sum(), arms }
+}
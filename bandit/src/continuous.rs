@@ -0,0 +1,478 @@
+use std::vec::Vec;
+use rand::{Rng, RngCore};
+use rand::random;
+use serde::Serialize;
+use crate::agent::argmax;
+use crate::rng::{RandomSource, ReplayRng, SlotRng, StdSource};
+
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Domain {
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+impl Domain {
+    pub fn new(lower: Vec<f64>, upper: Vec<f64>) -> Result<Domain, String> {
+        if lower.is_empty() || lower.len() != upper.len() {
+            return Err("lower and upper bounds must have the same, non-zero dimension".to_string())
+        }
+        if lower.iter().zip(upper.iter()).any(|(l, u)| l.is_nan() || u.is_nan() || l >= u) {
+            return Err("each lower bound must be below its upper bound".to_string())
+        }
+        Ok(Domain { lower, upper })
+    }
+    pub fn unit(dim: usize) -> Domain {
+        Domain { lower: vec![0.; dim], upper: vec![1.; dim] }
+    }
+    pub fn dim(&self) -> usize {
+        self.lower.len()
+    }
+    pub fn width(&self, i: usize) -> f64 {
+        self.upper[i] - self.lower[i]
+    }
+    pub fn center(&self) -> Vec<f64> {
+        (0..self.dim()).map(|i| (self.lower[i] + self.upper[i]) / 2.).collect()
+    }
+    pub fn contains(&self, x: &[f64]) -> bool {
+        x.len() == self.dim() && (0..self.dim()).all(|i| x[i] >= self.lower[i] && x[i] <= self.upper[i])
+    }
+    pub fn sample(&self, rng: &mut dyn RngCore) -> Vec<f64> {
+        (0..self.dim()).map(|i| self.lower[i] + rng.gen::<f64>() * self.width(i)).collect()
+    }
+    pub fn split(&self) -> (Domain, Domain) {
+        let axis = (0..self.dim()).fold(0, |best, i| if self.width(i) > self.width(best) { i } else { best });
+        let middle = (self.lower[axis] + self.upper[axis]) / 2.;
+        let mut left = self.clone();
+        let mut right = self.clone();
+        left.upper[axis] = middle;
+        right.lower[axis] = middle;
+        (left, right)
+    }
+    pub fn grid(&self, points: usize) -> Vec<Vec<f64>> {
+        let per_axis = ((points as f64).powf(1. / self.dim() as f64).floor() as usize).max(1);
+        let mut grid = vec![vec![]];
+        for i in 0..self.dim() {
+            let values: Vec<f64> = (0..per_axis)
+                .map(|k| self.lower[i] + self.width(i) * (k as f64 + 0.5) / per_axis as f64)
+                .collect();
+            grid = grid.into_iter()
+                .flat_map(|prefix: Vec<f64>| values.iter().map(move |v| {
+                    let mut point = prefix.clone();
+                    point.push(*v);
+                    point
+                }))
+                .collect();
+        }
+        grid
+    }
+    pub fn parse_point(&self, text: &str) -> Result<Vec<f64>, String> {
+        let x = text.split(',')
+            .map(|v| v.trim().parse::<f64>().map_err(|e| format!("{}: {}", v, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        if !self.contains(&x) {
+            return Err(format!("x must be a {}-dimensional point in {:?}..{:?}", self.dim(), self.lower, self.upper))
+        }
+        Ok(x)
+    }
+}
+
+pub fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Peak {
+    pub center: Vec<f64>,
+    pub height: f64,
+}
+
+pub struct ContinuousGame {
+    domain: Domain,
+    lipschitz: f64,
+    peaks: Vec<Peak>,
+    rng: ReplayRng,
+    horizon: Option<usize>,
+    playing: bool,
+    history: Vec<(Vec<f64>, f64)>,
+}
+
+impl ContinuousGame {
+    pub fn new(dim: usize) -> ContinuousGame {
+        ContinuousGame::seeded(Domain::unit(dim), 1., 3, random())
+    }
+    pub fn seeded(domain: Domain, lipschitz: f64, n_peaks: usize, seed: u64) -> ContinuousGame {
+        ContinuousGame::with_source(domain, lipschitz, n_peaks, seed, &StdSource)
+    }
+    pub fn with_source(domain: Domain, lipschitz: f64, n_peaks: usize, seed: u64, source: &dyn RandomSource) -> ContinuousGame {
+        let mut rng: Box<dyn SlotRng> = source.generator(seed);
+        let peaks = (0..n_peaks.max(1))
+            .map(|_| Peak { center: domain.sample(rng.as_mut()), height: rng.gen_range(0.3, 0.9) })
+            .collect();
+        let rng = ReplayRng::new(source.generator(rng.gen()));
+        ContinuousGame { domain, lipschitz, peaks, rng, horizon: None, playing: false, history: vec![] }
+    }
+    pub fn domain(&self) -> &Domain {
+        &self.domain
+    }
+    pub fn lipschitz(&self) -> f64 {
+        self.lipschitz
+    }
+    pub fn mean(&self, x: &[f64]) -> f64 {
+        self.peaks.iter()
+            .map(|p| p.height - self.lipschitz * distance(&p.center, x))
+            .fold(0., f64::max)
+    }
+    pub fn best(&self) -> f64 {
+        self.peaks.iter().map(|p| p.height).fold(0., f64::max)
+    }
+    pub fn start(&mut self, horizon: Option<usize>) {
+        self.horizon = horizon;
+        self.playing = horizon != Some(0);
+        self.history.clear();
+        self.rng.rewind();
+    }
+    pub fn play(&mut self, x: &[f64]) -> Result<f64, String> {
+        if !self.playing {
+            return Err("A game is not started. Please start a game.".to_string())
+        }
+        if !self.domain.contains(x) {
+            return Err(format!("x must be a {}-dimensional point in {:?}..{:?}", self.domain.dim(), self.domain.lower, self.domain.upper))
+        }
+        let reward = (self.rng.gen::<f64>() < self.mean(x)) as u32 as f64;
+        self.history.push((x.to_vec(), reward));
+        if self.horizon.map(|h| self.history.len() >= h).unwrap_or(false) {
+            self.playing = false;
+        }
+        Ok(reward)
+    }
+    pub fn finish(&mut self) {
+        self.playing = false;
+    }
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+    pub fn history(&self) -> &[(Vec<f64>, f64)] {
+        &self.history
+    }
+    pub fn score(&self) -> f64 {
+        self.history.iter().map(|(_, r)| r).sum()
+    }
+    pub fn regret(&self) -> f64 {
+        self.history.iter().map(|(x, _)| self.best() - self.mean(x)).sum()
+    }
+}
+
+
+pub trait ContinuousAgent {
+    fn name(&self) -> String;
+    fn select(&mut self, rng: &mut dyn RngCore) -> Vec<f64>;
+    fn update(&mut self, x: &[f64], reward: f64);
+    fn reset(&mut self);
+}
+
+
+struct ActiveArm {
+    x: Vec<f64>,
+    count: usize,
+    sum: f64,
+}
+
+pub struct Zooming {
+    domain: Domain,
+    horizon: usize,
+    candidates: usize,
+    arms: Vec<ActiveArm>,
+}
+
+impl Zooming {
+    pub fn new(domain: Domain, horizon: usize) -> Zooming {
+        Zooming { domain, horizon, candidates: 64, arms: vec![] }
+    }
+    fn radius(&self, count: usize) -> f64 {
+        (2. * (self.horizon.max(2) as f64).ln() / (count + 1) as f64).sqrt()
+    }
+    fn covered(&self, x: &[f64]) -> bool {
+        self.arms.iter().any(|a| distance(&a.x, x) <= self.radius(a.count))
+    }
+}
+
+impl ContinuousAgent for Zooming {
+    fn name(&self) -> String {
+        "zooming".to_string()
+    }
+    fn select(&mut self, rng: &mut dyn RngCore) -> Vec<f64> {
+        for _ in 0..self.candidates {
+            let x = self.domain.sample(rng);
+            if !self.covered(&x) {
+                self.arms.push(ActiveArm { x, count: 0, sum: 0. });
+                break
+            }
+        }
+        let indices: Vec<f64> = self.arms.iter().map(|a| {
+            let mean = if a.count == 0 { 0. } else { a.sum / a.count as f64 };
+            mean + 2. * self.radius(a.count)
+        }).collect();
+        self.arms[argmax(&indices)].x.clone()
+    }
+    fn update(&mut self, x: &[f64], reward: f64) {
+        if let Some(arm) = self.arms.iter_mut().find(|a| a.x.as_slice() == x) {
+            arm.count += 1;
+            arm.sum += reward;
+        }
+    }
+    fn reset(&mut self) {
+        self.arms.clear();
+    }
+}
+
+
+struct Node {
+    cell: Domain,
+    depth: i32,
+    count: usize,
+    sum: f64,
+    children: Option<(usize, usize)>,
+    b: f64,
+}
+
+pub struct Hoo {
+    domain: Domain,
+    nu: f64,
+    rho: f64,
+    horizon: usize,
+    nodes: Vec<Node>,
+    path: Vec<usize>,
+}
+
+impl Hoo {
+    pub fn new(domain: Domain, nu: f64, rho: f64, horizon: usize) -> Hoo {
+        let mut hoo = Hoo { domain, nu, rho, horizon, nodes: vec![], path: vec![] };
+        hoo.reset();
+        hoo
+    }
+    fn leaf(cell: Domain, depth: i32) -> Node {
+        Node { cell, depth, count: 0, sum: 0., children: None, b: f64::INFINITY }
+    }
+    // The confidence term uses the known horizon rather than the round, so a
+    // node's B-value only changes when its own subtree is played and only the
+    // played path has to be refreshed, from the leaf up.
+    fn refresh(&mut self) {
+        let log = (self.horizon.max(2) as f64).ln();
+        for index in self.path.iter().rev() {
+            let child_b = match self.nodes[*index].children {
+                Some((left, right)) => self.nodes[left].b.max(self.nodes[right].b),
+                None => f64::INFINITY,
+            };
+            let node = &mut self.nodes[*index];
+            let u = if node.count == 0 {
+                f64::INFINITY
+            } else {
+                node.sum / node.count as f64
+                    + (2. * log / node.count as f64).sqrt()
+                    + self.nu * self.rho.powi(node.depth)
+            };
+            node.b = u.min(child_b);
+        }
+    }
+}
+
+impl ContinuousAgent for Hoo {
+    fn name(&self) -> String {
+        format!("hoo({}, {})", self.nu, self.rho)
+    }
+    fn select(&mut self, rng: &mut dyn RngCore) -> Vec<f64> {
+        let mut index = 0;
+        self.path = vec![0];
+        while let Some((left, right)) = self.nodes[index].children {
+            let (bl, br) = (self.nodes[left].b, self.nodes[right].b);
+            index = if bl > br || (bl == br && rng.gen::<bool>()) { left } else { right };
+            self.path.push(index);
+        }
+        let (left, right) = self.nodes[index].cell.split();
+        let depth = self.nodes[index].depth + 1;
+        self.nodes.push(Hoo::leaf(left, depth));
+        self.nodes.push(Hoo::leaf(right, depth));
+        let children = (self.nodes.len() - 2, self.nodes.len() - 1);
+        self.nodes[index].children = Some(children);
+        let chosen = if rng.gen::<bool>() { children.0 } else { children.1 };
+        self.path.push(chosen);
+        self.nodes[chosen].cell.sample(rng)
+    }
+    fn update(&mut self, _x: &[f64], reward: f64) {
+        for index in self.path.iter() {
+            self.nodes[*index].count += 1;
+            self.nodes[*index].sum += reward;
+        }
+        self.refresh();
+    }
+    fn reset(&mut self) {
+        self.nodes = vec![Hoo::leaf(self.domain.clone(), 0)];
+        self.path.clear();
+    }
+}
+
+
+pub struct GpUcb {
+    candidates: Vec<Vec<f64>>,
+    lengthscale: f64,
+    noise: f64,
+    delta: f64,
+    mean: Vec<f64>,
+    covariance: Vec<Vec<f64>>,
+    total: usize,
+}
+
+impl GpUcb {
+    pub fn new(domain: Domain, points: usize, lengthscale: f64) -> GpUcb {
+        let candidates = domain.grid(points);
+        let mut gp = GpUcb {
+            candidates, lengthscale, noise: 0.25, delta: 0.1,
+            mean: vec![], covariance: vec![], total: 0,
+        };
+        gp.reset();
+        gp
+    }
+    fn kernel(&self, a: &[f64], b: &[f64]) -> f64 {
+        (-distance(a, b).powi(2) / (2. * self.lengthscale.powi(2))).exp()
+    }
+    pub fn beta(&self) -> f64 {
+        let t = (self.total + 1) as f64;
+        let m = self.candidates.len() as f64;
+        2. * (m * t * t * std::f64::consts::PI.powi(2) / (6. * self.delta)).ln()
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        let beta = self.beta();
+        (0..self.candidates.len())
+            .map(|i| self.mean[i] + (beta * self.covariance[i][i].max(0.)).sqrt())
+            .collect()
+    }
+}
+
+impl ContinuousAgent for GpUcb {
+    fn name(&self) -> String {
+        format!("gp-ucb({})", self.lengthscale)
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        self.candidates[argmax(&self.indices())].clone()
+    }
+    fn update(&mut self, x: &[f64], reward: f64) {
+        self.total += 1;
+        let j = match self.candidates.iter().position(|c| c.as_slice() == x) {
+            Some(j) => j,
+            None => return
+        };
+        let denominator = self.covariance[j][j] + self.noise;
+        let column: Vec<f64> = self.covariance.iter().map(|row| row[j]).collect();
+        let residual = reward - self.mean[j];
+        for i in 0..self.candidates.len() {
+            self.mean[i] += column[i] * residual / denominator;
+            for k in 0..self.candidates.len() {
+                self.covariance[i][k] -= column[i] * column[k] / denominator;
+            }
+        }
+    }
+    fn reset(&mut self) {
+        let n = self.candidates.len();
+        self.mean = vec![0.; n];
+        self.covariance = (0..n)
+            .map(|i| (0..n).map(|k| self.kernel(&self.candidates[i], &self.candidates[k])).collect())
+            .collect();
+        self.total = 0;
+    }
+}
+
+
+pub fn by_name(name: &str, domain: &Domain, horizon: usize) -> Result<Box<dyn ContinuousAgent + Send>, String> {
+    let mut parts = name.split(':');
+    let kind = parts.next().unwrap_or("");
+    let params = parts.map(|x| x.parse::<f64>().map_err(|e| format!("{}: {}", name, e))).collect::<Result<Vec<f64>, String>>()?;
+    let param = |i: usize, default: f64| params.get(i).cloned().unwrap_or(default);
+    match kind {
+        "zooming" => Ok(Box::new(Zooming::new(domain.clone(), horizon))),
+        "hoo" => Ok(Box::new(Hoo::new(domain.clone(), param(0, 1.), param(1, 0.5), horizon))),
+        "gp-ucb" => Ok(Box::new(GpUcb::new(domain.clone(), param(1, 100.) as usize, param(0, 0.1)))),
+        _ => Err(format!("Unknown continuous agent: {}", name))
+    }
+}
+
+pub fn run(agent: &mut dyn ContinuousAgent, game: &mut ContinuousGame, horizon: usize, stride: usize, rng: &mut dyn RngCore) -> Result<Vec<f64>, String> {
+    let stride = stride.max(1);
+    let best = game.best();
+    let mut regret = 0.;
+    let mut curve = Vec::with_capacity(horizon / stride + 1);
+    agent.reset();
+    game.start(None);
+    for t in 1..=horizon {
+        let x = agent.select(rng);
+        let reward = game.play(&x)?;
+        agent.update(&x, reward);
+        regret += best - game.mean(&x);
+        if t % stride == 0 || t == horizon {
+            curve.push(regret);
+        }
+    }
+    Ok(curve)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::*;
+
+    fn near_peak(name: &str) -> f64 {
+        let domain = Domain::unit(1);
+        let mut game = ContinuousGame::seeded(domain.clone(), 1., 1, 0);
+        let mut agent = by_name(name, &domain, 3000).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        run(agent.as_mut(), &mut game, 3000, 3000, &mut rng).unwrap();
+        let late = &game.history()[1500..];
+        late.iter().filter(|(x, _)| game.best() - game.mean(x) < 0.1).count() as f64 / late.len() as f64
+    }
+
+    #[test]
+    fn zooming_concentrates_near_the_peak() {
+        assert!(near_peak("zooming") > 0.5);
+    }
+
+    #[test]
+    fn hoo_concentrates_near_the_peak() {
+        assert!(near_peak("hoo") > 0.5);
+    }
+
+    #[test]
+    fn gp_ucb_concentrates_near_the_peak() {
+        assert!(near_peak("gp-ucb") > 0.5);
+    }
+
+    fn full_b(hoo: &Hoo, index: usize) -> f64 {
+        let node = &hoo.nodes[index];
+        let child_b = match node.children {
+            Some((left, right)) => full_b(hoo, left).max(full_b(hoo, right)),
+            None => f64::INFINITY,
+        };
+        let u = if node.count == 0 {
+            f64::INFINITY
+        } else {
+            node.sum / node.count as f64
+                + (2. * (hoo.horizon as f64).ln() / node.count as f64).sqrt()
+                + hoo.nu * hoo.rho.powi(node.depth)
+        };
+        u.min(child_b)
+    }
+
+    #[test]
+    fn hoo_path_refresh_matches_a_full_recompute() {
+        let domain = Domain::unit(2);
+        let mut game = ContinuousGame::seeded(domain.clone(), 1., 2, 3);
+        let mut hoo = Hoo::new(domain, 1., 0.5, 500);
+        let mut rng = StdRng::seed_from_u64(3);
+        run(&mut hoo, &mut game, 500, 500, &mut rng).unwrap();
+        for index in 0..hoo.nodes.len() {
+            assert_eq!(hoo.nodes[index].b, full_b(&hoo, index));
+        }
+    }
+}
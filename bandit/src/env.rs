@@ -0,0 +1,145 @@
+use std::vec::Vec;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::Serialize;
+use crate::game::Game;
+
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Observation {
+    pub round: usize,
+    pub n_arms: usize,
+    pub context: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Info {
+    pub expected_reward: f64,
+    pub best_expected_reward: f64,
+    pub regret: f64,
+    pub score: f64,
+}
+
+pub type Step = (Observation, f64, bool, Info);
+
+pub trait Environment {
+    fn n_actions(&self) -> usize;
+    fn context_dim(&self) -> usize {
+        0
+    }
+    fn horizon(&self) -> Option<usize>;
+    fn reset(&mut self) -> Observation;
+    fn step(&mut self, action: usize) -> Result<Step, String>;
+}
+
+fn best(values: &[f64]) -> f64 {
+    values.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+}
+
+
+impl Environment for Game {
+    fn n_actions(&self) -> usize {
+        self.n_machines()
+    }
+    fn horizon(&self) -> Option<usize> {
+        Game::horizon(self)
+    }
+    fn reset(&mut self) -> Observation {
+        let horizon = Game::horizon(self);
+        Game::reset(self);
+        self.start(horizon);
+        Observation { round: 0, n_arms: self.n_machines(), context: vec![] }
+    }
+    fn step(&mut self, action: usize) -> Result<Step, String> {
+        let reward = self.play(action)?;
+        let profiles = self.profiles();
+        let info = Info {
+            expected_reward: profiles[action],
+            best_expected_reward: best(&profiles),
+            regret: best(&profiles) - profiles[action],
+            score: self.score(),
+        };
+        let observation = Observation { round: self.play_count(), n_arms: self.n_machines(), context: vec![] };
+        Ok((observation, reward, !self.is_playing(), info))
+    }
+}
+
+
+pub struct ContextualGame {
+    weights: Vec<Vec<f64>>,
+    horizon: Option<usize>,
+    seed: u64,
+    rng: StdRng,
+    context: Vec<f64>,
+    round: usize,
+    score: f64,
+}
+
+impl ContextualGame {
+    pub fn seeded(n_arms: usize, dim: usize, horizon: Option<usize>, seed: u64) -> ContextualGame {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let weights = (0..n_arms)
+            .map(|_| (0..dim + 1).map(|_| rng.gen_range(-2., 2.)).collect())
+            .collect();
+        let seed = rng.gen();
+        ContextualGame {
+            weights,
+            horizon,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            context: vec![],
+            round: 0,
+            score: 0.,
+        }
+    }
+    pub fn probabilities(&self, context: &[f64]) -> Vec<f64> {
+        self.weights.iter().map(|w| {
+            let z = w[0] + w[1..].iter().zip(context.iter()).map(|(w, x)| w * x).sum::<f64>();
+            1. / (1. + (-z).exp())
+        }).collect()
+    }
+    fn observe(&mut self) -> Observation {
+        let dim = self.context_dim();
+        self.context = (0..dim).map(|_| self.rng.gen()).collect();
+        Observation { round: self.round, n_arms: self.weights.len(), context: self.context.clone() }
+    }
+}
+
+impl Environment for ContextualGame {
+    fn n_actions(&self) -> usize {
+        self.weights.len()
+    }
+    fn context_dim(&self) -> usize {
+        self.weights.first().map(|w| w.len() - 1).unwrap_or(0)
+    }
+    fn horizon(&self) -> Option<usize> {
+        self.horizon
+    }
+    fn reset(&mut self) -> Observation {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.round = 0;
+        self.score = 0.;
+        self.observe()
+    }
+    fn step(&mut self, action: usize) -> Result<Step, String> {
+        if self.context.is_empty() && self.context_dim() > 0 {
+            return Err("The environment is not reset. Please reset it.".to_string())
+        }
+        if self.horizon.map(|h| self.round >= h).unwrap_or(false) {
+            return Err("The episode is over. Please reset it.".to_string())
+        }
+        let probs = self.probabilities(&self.context);
+        let p = *probs.get(action).ok_or("Index out of range.".to_string())?;
+        let reward = (self.rng.gen::<f64>() < p) as u32 as f64;
+        self.round += 1;
+        self.score += reward;
+        let info = Info {
+            expected_reward: p,
+            best_expected_reward: best(&probs),
+            regret: best(&probs) - p,
+            score: self.score,
+        };
+        let done = self.horizon.map(|h| self.round >= h).unwrap_or(false);
+        Ok((self.observe(), reward, done, info))
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+use std::vec::Vec;
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+use crate::agent::{argmax, one_hot, sample_index, Agent, ArmStats};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedbackGraph {
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl FeedbackGraph {
+    pub fn bandit(n_arms: usize) -> FeedbackGraph {
+        FeedbackGraph { neighbors: (0..n_arms).map(|i| vec![i]).collect() }
+    }
+    pub fn full(n_arms: usize) -> FeedbackGraph {
+        FeedbackGraph { neighbors: (0..n_arms).map(|_| (0..n_arms).collect()).collect() }
+    }
+    pub fn random(n_arms: usize, p: f64, rng: &mut dyn RngCore) -> FeedbackGraph {
+        let neighbors = (0..n_arms)
+            .map(|i| (0..n_arms).filter(|j| *j == i || rng.gen::<f64>() < p).collect())
+            .collect();
+        FeedbackGraph { neighbors }
+    }
+    pub fn from_edges(n_arms: usize, edges: &[(usize, usize)]) -> Result<FeedbackGraph, String> {
+        let mut graph = FeedbackGraph::bandit(n_arms);
+        for (from, to) in edges {
+            if *from >= n_arms || *to >= n_arms {
+                return Err(format!("edge ({}, {}) is out of range for {} arms", from, to, n_arms))
+            }
+            if !graph.neighbors[*from].contains(to) {
+                graph.neighbors[*from].push(*to);
+            }
+        }
+        graph.neighbors.iter_mut().for_each(|x| x.sort());
+        Ok(graph)
+    }
+    pub fn n_arms(&self) -> usize {
+        self.neighbors.len()
+    }
+    pub fn neighbors(&self, arm: usize) -> &[usize] {
+        &self.neighbors[arm]
+    }
+    pub fn independence_number(&self) -> usize {
+        let n = self.n_arms();
+        let linked = |i: usize, j: usize| self.neighbors[i].contains(&j) || self.neighbors[j].contains(&i);
+        let mut remaining: Vec<usize> = (0..n).collect();
+        let mut size = 0;
+        while !remaining.is_empty() {
+            let degree = |i: usize| remaining.iter().filter(|j| **j != i && linked(i, **j)).count();
+            let pick = *remaining.iter().min_by_key(|i| degree(**i)).unwrap();
+            remaining.retain(|j| *j != pick && !linked(pick, *j));
+            size += 1;
+        }
+        size
+    }
+    pub fn observation_probs(&self, probs: &[f64]) -> Vec<f64> {
+        let mut q = vec![0.; self.n_arms()];
+        for (i, p) in probs.iter().enumerate() {
+            self.neighbors[i].iter().for_each(|j| q[*j] += p);
+        }
+        q
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphKind {
+    Bandit,
+    Full,
+    Random(f64),
+}
+
+impl GraphKind {
+    pub fn build(&self, n_arms: usize, seed: u64) -> FeedbackGraph {
+        match self {
+            GraphKind::Bandit => FeedbackGraph::bandit(n_arms),
+            GraphKind::Full => FeedbackGraph::full(n_arms),
+            GraphKind::Random(p) => FeedbackGraph::random(n_arms, *p, &mut StdRng::seed_from_u64(seed.rotate_left(16))),
+        }
+    }
+}
+
+impl fmt::Display for GraphKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphKind::Bandit => write!(f, "bandit"),
+            GraphKind::Full => write!(f, "full"),
+            GraphKind::Random(p) => write!(f, "random({})", p),
+        }
+    }
+}
+
+pub fn graph_by_name(name: &str, edge_prob: f64) -> Result<GraphKind, String> {
+    match name {
+        "bandit" => Ok(GraphKind::Bandit),
+        "full" => Ok(GraphKind::Full),
+        "random" if (0. ..=1.).contains(&edge_prob) => Ok(GraphKind::Random(edge_prob)),
+        "random" => Err(format!("edge probability must be in [0, 1], got {}", edge_prob)),
+        _ => Err(format!("Unknown graph: {}", name))
+    }
+}
+
+
+pub struct UcbN {
+    stats: ArmStats,
+    plays: usize,
+}
+
+impl UcbN {
+    pub fn new(n_arms: usize) -> UcbN {
+        UcbN { stats: ArmStats::new(n_arms), plays: 0 }
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        let total = self.plays.max(1) as f64;
+        (0..self.stats.n_arms()).map(|i| match self.stats.counts[i] {
+            0 => f64::INFINITY,
+            n => self.stats.mean(i) + (2. * total.ln() / n as f64).sqrt(),
+        }).collect()
+    }
+}
+
+impl Agent for UcbN {
+    fn name(&self) -> String {
+        "ucb-n".to_string()
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        one_hot(self.stats.n_arms(), argmax(&self.indices()))
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        argmax(&self.indices())
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.plays += 1;
+        self.stats.update(arm, reward)
+    }
+    fn observe(&mut self, arm: usize, reward: f64) {
+        self.stats.update(arm, reward)
+    }
+    fn reset(&mut self) {
+        self.stats.reset();
+        self.plays = 0;
+    }
+}
+
+
+pub struct Exp3Set {
+    graph: FeedbackGraph,
+    eta: f64,
+    log_weights: Vec<f64>,
+    last: Vec<f64>,
+}
+
+impl Exp3Set {
+    pub fn new(graph: FeedbackGraph, eta: f64) -> Exp3Set {
+        let n = graph.n_arms();
+        Exp3Set { graph, eta, log_weights: vec![0.; n], last: vec![1. / n as f64; n] }
+    }
+    pub fn tuned(graph: FeedbackGraph, horizon: usize) -> Exp3Set {
+        let n = graph.n_arms().max(2) as f64;
+        let alpha = graph.independence_number().max(1) as f64;
+        let eta = (2. * n.ln() / (horizon.max(1) as f64 * alpha)).sqrt();
+        Exp3Set::new(graph, eta)
+    }
+    fn weights(&self) -> Vec<f64> {
+        let top = self.log_weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let w: Vec<f64> = self.log_weights.iter().map(|x| (x - top).exp()).collect();
+        let total: f64 = w.iter().sum();
+        w.into_iter().map(|x| x / total).collect()
+    }
+}
+
+impl Agent for Exp3Set {
+    fn name(&self) -> String {
+        format!("exp3-set({})", self.eta)
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        self.last = self.weights();
+        self.last.clone()
+    }
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        let probs = self.probs(rng);
+        sample_index(&probs, rng)
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.observe(arm, reward)
+    }
+    fn observe(&mut self, arm: usize, reward: f64) {
+        let q = self.graph.observation_probs(&self.last)[arm];
+        let loss = 1. - reward.clamp(0., 1.);
+        self.log_weights[arm] -= self.eta * loss / q.max(1e-12);
+    }
+    fn reset(&mut self) {
+        *self = Exp3Set::new(self.graph.clone(), self.eta);
+    }
+}
+
+
+pub fn by_name(name: &str, graph: &FeedbackGraph, horizon: usize) -> Result<Box<dyn Agent + Send>, String> {
+    let mut parts = name.splitn(2, ':');
+    let kind = parts.next().unwrap_or("");
+    let param = parts.next().map(|x| x.parse::<f64>().map_err(|e| format!("{}: {}", name, e))).transpose()?;
+    match (kind, param) {
+        ("ucb-n", _) => Ok(Box::new(UcbN::new(graph.n_arms()))),
+        ("exp3-set", Some(eta)) => Ok(Box::new(Exp3Set::new(graph.clone(), eta))),
+        ("exp3-set", None) => Ok(Box::new(Exp3Set::tuned(graph.clone(), horizon))),
+        _ => crate::agent::by_name(name, graph.n_arms())
+    }
+}
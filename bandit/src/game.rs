@@ -1,93 +1,504 @@
 use std::{usize, vec::Vec};
 use std::any::Any;
-use statrs::distribution::Beta;
+use std::str::FromStr;
+use std::sync::Arc;
+use statrs::distribution::{Normal, Pareto, StudentsT};
 use rand::random;
 use rand::distributions::Distribution;
-use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng, thread_rng};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::markov::{MarkovArm, MarkovSlot};
+use crate::registry::{self, SlotParams, SlotRegistry};
+use crate::risk::Objective;
+use crate::rng::{RandomSource, ReplayRng, SlotRng, StdSource};
+use crate::spec::{ArmSpec, GameSpec};
 
 
-trait Replayable {
-    fn as_any(&self) -> &Any;
+pub trait Replayable {
+    fn as_any(&self) -> &dyn Any;
+    fn box_clone(&self) -> Box<dyn Replayable + Send>;
     fn initialize(&mut self);
     fn play(&mut self) -> f64;
+    fn peek(&self, rng: &mut dyn SlotRng) -> f64;
     fn profile(&self) -> f64;
+    fn advance(&mut self, _round: usize) {}
+    fn reseed(&mut self, _source: &dyn RandomSource, _seed: u64) {}
+    fn play_vector(&mut self) -> Vec<f64> {
+        vec![self.play()]
+    }
+    fn profile_vector(&self) -> Vec<f64> {
+        vec![self.profile()]
+    }
+    fn markov(&self) -> Option<(&MarkovArm, usize)> {
+        None
+    }
+}
+
+impl Clone for Box<dyn Replayable + Send> {
+    fn clone(&self) -> Box<dyn Replayable + Send> {
+        self.box_clone()
+    }
 }
 
 
-#[derive(Debug, Clone)]
-struct BinarySlot {
+#[derive(Clone)]
+pub(crate) struct BinarySlot {
     prob: f64,
-    rng: StdRng,
-    seed: u64,
+    rng: ReplayRng,
 }
 
 impl Replayable for BinarySlot {
-    fn as_any(&self) -> &Any {
+    fn as_any(&self) -> &dyn Any {
         self
     }
+    fn box_clone(&self) -> Box<dyn Replayable + Send> {
+        Box::new(self.clone())
+    }
     fn initialize(&mut self) {
-        self.rng = StdRng::seed_from_u64(self.seed)
+        self.rng.rewind()
+    }
+    fn reseed(&mut self, source: &dyn RandomSource, seed: u64) {
+        self.rng = ReplayRng::new(source.generator(seed))
     }
     fn play(&mut self) -> f64 {
         (self.rng.gen::<f64>() < self.prob) as u32 as f64
     }
+    fn peek(&self, rng: &mut dyn SlotRng) -> f64 {
+        (rng.gen::<f64>() < self.prob) as u32 as f64
+    }
     fn profile(&self) -> f64 {
         self.prob
     }
 }
 
 impl BinarySlot {
-    fn new() -> BinarySlot {
-        let seed: u64 = random();
+    fn from_rng(rng: &mut dyn SlotRng, source: &dyn RandomSource) -> BinarySlot {
+        let seed: u64 = rng.gen();
         BinarySlot {
-            prob: random(),
-            rng: StdRng::seed_from_u64(seed),
-            seed: seed
+            prob: rng.gen(),
+            rng: ReplayRng::new(source.generator(seed)),
         }
     }
+    pub(crate) fn from_params(params: &SlotParams, rng: Box<dyn SlotRng>) -> Result<BinarySlot, String> {
+        registry::expect_keys(params, &["p"])?;
+        let prob = registry::param(params, "p")?;
+        if !(0. ..=1.).contains(&prob) {
+            return Err(format!("p must be in [0, 1], got {}", prob))
+        }
+        Ok(BinarySlot { prob, rng: ReplayRng::new(rng) })
+    }
 }
 
-trait Factory<T: Replayable> {
-    fn gen(&mut self) -> T; 
+
+pub const GAUSSIAN_STD: f64 = 1.;
+
+#[derive(Clone)]
+pub(crate) struct GaussianSlot {
+    mean: f64,
+    std: f64,
+    rng: ReplayRng,
+}
+
+impl Replayable for GaussianSlot {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn box_clone(&self) -> Box<dyn Replayable + Send> {
+        Box::new(self.clone())
+    }
+    fn initialize(&mut self) {
+        self.rng.rewind()
+    }
+    fn reseed(&mut self, source: &dyn RandomSource, seed: u64) {
+        self.rng = ReplayRng::new(source.generator(seed))
+    }
+    fn play(&mut self) -> f64 {
+        Normal::new(self.mean, self.std).unwrap().sample(&mut self.rng)
+    }
+    fn peek(&self, rng: &mut dyn SlotRng) -> f64 {
+        Normal::new(self.mean, self.std).unwrap().sample(rng)
+    }
+    fn profile(&self) -> f64 {
+        self.mean
+    }
 }
 
-trait Storage<T: Replayable + Clone + 'static> {
-    fn hist(&mut self) -> &Vec<Box<dyn Replayable + Send>>;
-    fn nth(&mut self, n: usize) -> Option<T> {
-        if let Some(val) = self.hist().get(n) {
-            Some(val.as_any().downcast_ref::<T>().unwrap().clone());
+impl GaussianSlot {
+    fn from_rng(rng: &mut dyn SlotRng, source: &dyn RandomSource) -> GaussianSlot {
+        let seed: u64 = rng.gen();
+        GaussianSlot {
+            mean: rng.gen(),
+            std: GAUSSIAN_STD,
+            rng: ReplayRng::new(source.generator(seed)),
         }
-        None
+    }
+    pub(crate) fn from_params(params: &SlotParams, rng: Box<dyn SlotRng>) -> Result<GaussianSlot, String> {
+        registry::expect_keys(params, &["mean", "std"])?;
+        let mean = registry::param(params, "mean")?;
+        let std = registry::param_or(params, "std", GAUSSIAN_STD);
+        if !mean.is_finite() {
+            return Err(format!("mean must be finite, got {}", mean))
+        }
+        if !(std > 0. && std.is_finite()) {
+            return Err(format!("std must be positive, got {}", std))
+        }
+        Ok(GaussianSlot { mean, std, rng: ReplayRng::new(rng) })
     }
 }
 
-struct SlotRepository {
-    storage: Vec<Box<dyn Replayable + Send>>
+
+#[derive(Clone)]
+pub(crate) struct ParetoSlot {
+    scale: f64,
+    shape: f64,
+    rng: ReplayRng,
 }
 
-impl SlotRepository {
-    fn new() -> SlotRepository {
-        SlotRepository{ storage: vec![] }
+impl Replayable for ParetoSlot {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn box_clone(&self) -> Box<dyn Replayable + Send> {
+        Box::new(self.clone())
+    }
+    fn initialize(&mut self) {
+        self.rng.rewind()
+    }
+    fn reseed(&mut self, source: &dyn RandomSource, seed: u64) {
+        self.rng = ReplayRng::new(source.generator(seed))
+    }
+    fn play(&mut self) -> f64 {
+        Pareto::new(self.scale, self.shape).unwrap().sample(&mut self.rng)
+    }
+    fn peek(&self, rng: &mut dyn SlotRng) -> f64 {
+        Pareto::new(self.scale, self.shape).unwrap().sample(rng)
+    }
+    fn profile(&self) -> f64 {
+        self.shape * self.scale / (self.shape - 1.)
     }
 }
 
-impl Factory<BinarySlot> for SlotRepository {
-    fn gen(&mut self) -> BinarySlot {
-        self.storage.push(Box::new(BinarySlot::new()));
-        self.storage.last_mut().unwrap().as_any().downcast_ref::<BinarySlot>().unwrap().clone()
+impl ParetoSlot {
+    fn from_rng(rng: &mut dyn SlotRng, source: &dyn RandomSource) -> ParetoSlot {
+        let seed: u64 = rng.gen();
+        ParetoSlot {
+            scale: rng.gen_range(0.1, 1.),
+            shape: rng.gen_range(1.5, 3.),
+            rng: ReplayRng::new(source.generator(seed)),
+        }
+    }
+    pub(crate) fn from_params(params: &SlotParams, rng: Box<dyn SlotRng>) -> Result<ParetoSlot, String> {
+        registry::expect_keys(params, &["scale", "shape"])?;
+        let scale = registry::param_or(params, "scale", 1.);
+        let shape = registry::param(params, "shape")?;
+        if !(scale > 0. && scale.is_finite()) {
+            return Err(format!("scale must be positive, got {}", scale))
+        }
+        if !(shape > 1. && shape.is_finite()) {
+            return Err(format!("shape must be greater than 1 for a finite mean, got {}", shape))
+        }
+        Ok(ParetoSlot { scale, shape, rng: ReplayRng::new(rng) })
     }
 }
 
-impl Storage<BinarySlot> for SlotRepository {
-    fn hist(&mut self) -> &Vec<Box<dyn Replayable + Send>> {
-        &mut self.storage
+
+#[derive(Clone)]
+pub(crate) struct StudentTSlot {
+    location: f64,
+    scale: f64,
+    df: f64,
+    rng: ReplayRng,
+}
+
+impl Replayable for StudentTSlot {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn box_clone(&self) -> Box<dyn Replayable + Send> {
+        Box::new(self.clone())
     }
-    fn nth(&mut self, n: usize) -> Option<BinarySlot> {
-        if let Some(val) = self.storage.get(n) {
-            Some(val.as_any().downcast_ref::<BinarySlot>().unwrap().clone());
+    fn initialize(&mut self) {
+        self.rng.rewind()
+    }
+    fn reseed(&mut self, source: &dyn RandomSource, seed: u64) {
+        self.rng = ReplayRng::new(source.generator(seed))
+    }
+    fn play(&mut self) -> f64 {
+        StudentsT::new(self.location, self.scale, self.df).unwrap().sample(&mut self.rng)
+    }
+    fn peek(&self, rng: &mut dyn SlotRng) -> f64 {
+        StudentsT::new(self.location, self.scale, self.df).unwrap().sample(rng)
+    }
+    fn profile(&self) -> f64 {
+        self.location
+    }
+}
+
+impl StudentTSlot {
+    fn from_rng(rng: &mut dyn SlotRng, source: &dyn RandomSource) -> StudentTSlot {
+        let seed: u64 = rng.gen();
+        StudentTSlot {
+            location: rng.gen(),
+            scale: 1.,
+            df: rng.gen_range(1.5, 3.),
+            rng: ReplayRng::new(source.generator(seed)),
+        }
+    }
+    pub(crate) fn from_params(params: &SlotParams, rng: Box<dyn SlotRng>) -> Result<StudentTSlot, String> {
+        registry::expect_keys(params, &["location", "scale", "df"])?;
+        let location = registry::param(params, "location")?;
+        let scale = registry::param_or(params, "scale", 1.);
+        let df = registry::param(params, "df")?;
+        if !location.is_finite() {
+            return Err(format!("location must be finite, got {}", location))
+        }
+        if !(scale > 0. && scale.is_finite()) {
+            return Err(format!("scale must be positive, got {}", scale))
+        }
+        if df.is_nan() || df <= 1. {
+            return Err(format!("df must be greater than 1 for a finite mean, got {}", df))
+        }
+        Ok(StudentTSlot { location, scale, df, rng: ReplayRng::new(rng) })
+    }
+}
+
+
+#[derive(Clone)]
+pub(crate) struct VectorSlot {
+    components: Vec<Box<dyn Replayable + Send>>,
+}
+
+impl Replayable for VectorSlot {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn box_clone(&self) -> Box<dyn Replayable + Send> {
+        Box::new(self.clone())
+    }
+    fn initialize(&mut self) {
+        self.components.iter_mut().for_each(|x| x.initialize());
+    }
+    fn reseed(&mut self, source: &dyn RandomSource, seed: u64) {
+        let mut rng = source.generator(seed);
+        self.components.iter_mut().for_each(|x| x.reseed(source, rng.gen()));
+    }
+    fn advance(&mut self, round: usize) {
+        self.components.iter_mut().for_each(|x| x.advance(round));
+    }
+    fn play(&mut self) -> f64 {
+        self.play_vector()[0]
+    }
+    fn peek(&self, rng: &mut dyn SlotRng) -> f64 {
+        self.components[0].peek(rng)
+    }
+    fn profile(&self) -> f64 {
+        self.components[0].profile()
+    }
+    fn play_vector(&mut self) -> Vec<f64> {
+        self.components.iter_mut().map(|x| x.play()).collect()
+    }
+    fn profile_vector(&self) -> Vec<f64> {
+        self.components.iter().map(|x| x.profile()).collect()
+    }
+}
+
+impl VectorSlot {
+    pub(crate) fn new(components: Vec<Box<dyn Replayable + Send>>) -> Result<VectorSlot, String> {
+        if components.is_empty() {
+            return Err("a vector arm needs at least one component".to_string())
+        }
+        Ok(VectorSlot { components })
+    }
+    fn from_rng(dim: usize, rng: &mut dyn SlotRng, source: &dyn RandomSource) -> VectorSlot {
+        let components = (0..dim).map(|_| Box::new(BinarySlot::from_rng(rng, source)) as Box<dyn Replayable + Send>).collect();
+        VectorSlot { components }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RewardType {
+    Binary,
+    Gaussian,
+    Pareto,
+    #[serde(rename = "student-t")]
+    StudentT,
+    #[serde(rename = "multi-binary")]
+    MultiBinary,
+    Markov,
+}
+
+impl RewardType {
+    fn gen(&self, rng: &mut dyn SlotRng, source: &dyn RandomSource) -> Box<dyn Replayable + Send> {
+        match self {
+            RewardType::Binary => Box::new(BinarySlot::from_rng(rng, source)),
+            RewardType::Gaussian => Box::new(GaussianSlot::from_rng(rng, source)),
+            RewardType::Pareto => Box::new(ParetoSlot::from_rng(rng, source)),
+            RewardType::StudentT => Box::new(StudentTSlot::from_rng(rng, source)),
+            RewardType::MultiBinary => Box::new(VectorSlot::from_rng(2, rng, source)),
+            RewardType::Markov => Box::new(MarkovSlot::from_rng(rng, source)),
+        }
+    }
+}
+
+impl FromStr for RewardType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<RewardType, String> {
+        match s {
+            "binary" | "bernoulli" => Ok(RewardType::Binary),
+            "gaussian" | "normal" => Ok(RewardType::Gaussian),
+            "pareto" => Ok(RewardType::Pareto),
+            "student-t" | "studentt" | "t" => Ok(RewardType::StudentT),
+            "multi-binary" => Ok(RewardType::MultiBinary),
+            "markov" => Ok(RewardType::Markov),
+            _ => Err(format!("Unknown reward type: {}", s))
+        }
+    }
+}
+
+impl std::fmt::Display for RewardType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RewardType::Binary => write!(f, "binary"),
+            RewardType::Gaussian => write!(f, "gaussian"),
+            RewardType::Pareto => write!(f, "pareto"),
+            RewardType::StudentT => write!(f, "student-t"),
+            RewardType::MultiBinary => write!(f, "multi-binary"),
+            RewardType::Markov => write!(f, "markov"),
         }
-        None
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EpisodeMode {
+    Replay,
+    Fresh,
+}
+
+impl FromStr for EpisodeMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<EpisodeMode, String> {
+        match s {
+            "replay" => Ok(EpisodeMode::Replay),
+            "fresh" => Ok(EpisodeMode::Fresh),
+            _ => Err(format!("Unknown episode mode: {}", s))
+        }
+    }
+}
+
+
+#[derive(Clone)]
+struct ScheduledSlot {
+    phases: Vec<(usize, Box<dyn Replayable + Send>)>,
+    current: usize,
+    cost: f64,
+}
+
+impl Replayable for ScheduledSlot {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn box_clone(&self) -> Box<dyn Replayable + Send> {
+        Box::new(self.clone())
+    }
+    fn initialize(&mut self) {
+        self.phases.iter_mut().for_each(|(_, x)| x.initialize());
+        self.current = 0;
+    }
+    fn play(&mut self) -> f64 {
+        self.phases[self.current].1.play() - self.cost
+    }
+    fn peek(&self, rng: &mut dyn SlotRng) -> f64 {
+        self.phases[self.current].1.peek(rng) - self.cost
+    }
+    fn profile(&self) -> f64 {
+        self.phases[self.current].1.profile() - self.cost
+    }
+    fn play_vector(&mut self) -> Vec<f64> {
+        let mut rewards = self.phases[self.current].1.play_vector();
+        rewards[0] -= self.cost;
+        rewards
+    }
+    fn profile_vector(&self) -> Vec<f64> {
+        let mut profile = self.phases[self.current].1.profile_vector();
+        profile[0] -= self.cost;
+        profile
+    }
+    fn markov(&self) -> Option<(&MarkovArm, usize)> {
+        self.phases[self.current].1.markov()
+    }
+    fn reseed(&mut self, source: &dyn RandomSource, seed: u64) {
+        let mut rng = source.generator(seed);
+        self.phases.iter_mut().for_each(|(_, x)| x.reseed(source, rng.gen()));
+    }
+    fn advance(&mut self, round: usize) {
+        while self.current + 1 < self.phases.len() && self.phases[self.current + 1].0 <= round {
+            self.current += 1;
+        }
+        self.phases[self.current].1.advance(round);
+    }
+}
+
+impl ScheduledSlot {
+    fn from_spec(spec: &ArmSpec, registry: &SlotRegistry, rng: &mut dyn SlotRng, source: &dyn RandomSource) -> Result<ScheduledSlot, String> {
+        spec.validate()?;
+        if let Some(slot) = registry.create_composite(spec, &mut || source.generator(rng.gen())) {
+            return Ok(ScheduledSlot { phases: vec![(0, slot?)], current: 0, cost: spec.cost })
+        }
+        let phases = spec.phases().into_iter().enumerate().map(|(i, (at, params))| {
+            match registry.create(&spec.kind, &params, source.generator(rng.gen())) {
+                Ok(slot) => Ok((at, slot)),
+                Err(e) if i == 0 => Err(e),
+                Err(e) => Err(format!("drift[{}]: {}", i - 1, e)),
+            }
+        }).collect::<Result<Vec<_>, String>>()?;
+        Ok(ScheduledSlot { phases, current: 0, cost: spec.cost })
+    }
+}
+
+
+pub trait Factory {
+    fn gen(&mut self, kind: &str, params: &SlotParams, rng: Box<dyn SlotRng>) -> Result<Box<dyn Replayable + Send>, String>;
+}
+
+pub trait Storage {
+    fn hist(&self) -> &Vec<Box<dyn Replayable + Send>>;
+    fn nth(&self, n: usize) -> Option<Box<dyn Replayable + Send>> {
+        self.hist().get(n).map(|x| x.box_clone())
+    }
+}
+
+pub struct SlotRepository {
+    registry: Arc<SlotRegistry>,
+    storage: Vec<Box<dyn Replayable + Send>>
+}
+
+impl SlotRepository {
+    pub fn new(registry: Arc<SlotRegistry>) -> SlotRepository {
+        SlotRepository{ registry, storage: vec![] }
+    }
+    pub fn add(&mut self, slot: Box<dyn Replayable + Send>) -> Box<dyn Replayable + Send> {
+        self.storage.push(slot.box_clone());
+        slot
+    }
+    pub fn registry(&self) -> &SlotRegistry {
+        &self.registry
+    }
+}
+
+impl Factory for SlotRepository {
+    fn gen(&mut self, kind: &str, params: &SlotParams, rng: Box<dyn SlotRng>) -> Result<Box<dyn Replayable + Send>, String> {
+        let slot = self.registry.create(kind, params, rng)?;
+        Ok(self.add(slot))
+    }
+}
+
+impl Storage for SlotRepository {
+    fn hist(&self) -> &Vec<Box<dyn Replayable + Send>> {
+        &self.storage
     }
 }
 
@@ -98,18 +509,22 @@ struct SlotMachine {
 }
 
 impl SlotMachine {
-    fn new() -> SlotMachine {
-        let mut repository = SlotRepository::new();
-        let slot = repository.gen();
+    fn with_slot(registry: &Arc<SlotRegistry>, slot: Box<dyn Replayable + Send>) -> SlotMachine {
+        let mut repository = SlotRepository::new(Arc::clone(registry));
+        let slot = repository.add(slot);
         SlotMachine {
-            slot: Box::new(slot),
+            slot,
             rewards: vec![],
             repository
         }
     }
-    fn play(&mut self) -> f64 {
-        let v = self.slot.play();
-        self.rewards.push(v);
+    fn from_spec(spec: &ArmSpec, registry: &Arc<SlotRegistry>, rng: &mut dyn SlotRng, source: &dyn RandomSource) -> Result<SlotMachine, String> {
+        let slot = ScheduledSlot::from_spec(spec, registry, rng, source)?;
+        Ok(SlotMachine::with_slot(registry, Box::new(slot)))
+    }
+    fn play(&mut self) -> Vec<f64> {
+        let v = self.slot.play_vector();
+        self.rewards.push(v[0]);
         v
     }    
     fn set(&mut self, slot: Box<dyn Replayable + Send>) {
@@ -118,11 +533,15 @@ impl SlotMachine {
     fn reset(&mut self) {
         self.slot.initialize()
     }
+    fn clear(&mut self) {
+        self.rewards.clear();
+        self.reset()
+    }
     fn set_nth(&mut self, index: usize) -> Result<(), String> {
         let new = self.repository.nth(index);
         match new {
             Some(slot) => {
-                self.set(Box::new(slot));
+                self.set(slot);
                 Ok(())
             }
             None => Err("Index out of range".to_string())
@@ -146,7 +565,7 @@ impl State {
     fn play(&self) -> State {
         match *self {
             State::Playing {cnt, max: Some(max)} => {
-                if cnt < max {
+                if cnt + 1 < max {
                     State::Playing { cnt: cnt + 1, max: Some(max)}
                 } else { State::End }
             }
@@ -156,46 +575,199 @@ impl State {
     }
 }
 
+fn observer_seed(seed: u64) -> u64 {
+    seed ^ 0x6F62_7365_7276_6572
+}
+
 pub struct Game {
     slot_machines: Vec<SlotMachine>,
     state: State,
-    scores: Vec<f64>
+    scores: Vec<f64>,
+    history: Vec<(usize, f64)>,
+    registry: Arc<SlotRegistry>,
+    source: Arc<dyn RandomSource>,
+    master: Box<dyn SlotRng>,
+    observer: ReplayRng,
+    mode: EpisodeMode,
+    horizon: Option<usize>,
+    limit: Option<usize>,
+    hints: bool,
+    hints_used: usize,
 }
 
 impl Game {
     pub fn new(n_machines: usize) -> Game {
-        let mut slot_machines = Vec::with_capacity(n_machines);
-        for _ in 0..n_machines {
-            slot_machines.push(SlotMachine::new())
-        }
+        Game::seeded(n_machines, RewardType::Binary, random())
+    }
+    pub fn seeded(n_machines: usize, reward_type: RewardType, seed: u64) -> Game {
+        Game::with_source(n_machines, reward_type, seed, Arc::new(StdSource))
+    }
+    pub fn with_source(n_machines: usize, reward_type: RewardType, seed: u64, source: Arc<dyn RandomSource>) -> Game {
+        let registry = Arc::new(SlotRegistry::default());
+        let mut rng = source.generator(seed);
+        let slot_machines = (0..n_machines)
+            .map(|_| SlotMachine::with_slot(&registry, reward_type.gen(rng.as_mut(), source.as_ref())))
+            .collect();
         Game {
             slot_machines,
             state: State::End,
             scores: Vec::new(),
+            history: Vec::new(),
+            registry,
+            observer: ReplayRng::new(source.generator(observer_seed(seed))),
+            source,
+            master: rng,
+            mode: EpisodeMode::Replay,
+            horizon: None,
+            limit: None,
+            hints: false,
+            hints_used: 0,
         }
     }
+    pub fn from_spec(spec: &GameSpec) -> Result<Game, String> {
+        Game::from_spec_with(spec, Arc::new(SlotRegistry::default()), Arc::new(StdSource))
+    }
+    pub fn from_spec_with(spec: &GameSpec, registry: Arc<SlotRegistry>, source: Arc<dyn RandomSource>) -> Result<Game, String> {
+        spec.validate()?;
+        let seed = spec.seed.unwrap_or_else(random);
+        let mut rng = source.generator(seed);
+        let slot_machines = spec.arms.iter().enumerate()
+            .map(|(i, arm)| {
+                SlotMachine::from_spec(arm, &registry, rng.as_mut(), source.as_ref())
+                    .map_err(|e| format!("{}: {}", arm.label(i), e))
+            })
+            .collect::<Result<Vec<SlotMachine>, String>>()?;
+        Ok(Game {
+            slot_machines,
+            state: State::End,
+            scores: Vec::new(),
+            history: Vec::new(),
+            registry,
+            observer: ReplayRng::new(source.generator(observer_seed(seed))),
+            source,
+            master: rng,
+            mode: EpisodeMode::Replay,
+            horizon: spec.horizon,
+            limit: spec.horizon,
+            hints: false,
+            hints_used: 0,
+        })
+    }
+    pub fn registry(&self) -> &SlotRegistry {
+        &self.registry
+    }
+    pub fn source(&self) -> &dyn RandomSource {
+        self.source.as_ref()
+    }
+    pub fn mode(&self) -> EpisodeMode {
+        self.mode
+    }
+    pub fn set_mode(&mut self, mode: EpisodeMode) {
+        self.mode = mode;
+    }
+    pub fn hints_enabled(&self) -> bool {
+        self.hints
+    }
+    pub fn set_hints(&mut self, enabled: bool) {
+        self.hints = enabled;
+    }
+    pub fn hints_used(&self) -> usize {
+        self.hints_used
+    }
+    pub fn record_hint(&mut self) {
+        self.hints_used += 1;
+    }
     pub fn start(&mut self, n_games: Option<usize>) {
-        self.state = State::new(n_games);
+        self.limit = n_games.or(self.horizon);
+        self.state = State::new(self.limit);
+        self.scores.clear();
+        self.history.clear();
+        self.hints_used = 0;
+        match self.mode {
+            EpisodeMode::Fresh => {
+                for machine in self.slot_machines.iter_mut() {
+                    machine.slot.reseed(self.source.as_ref(), self.master.gen());
+                }
+            }
+            EpisodeMode::Replay => self.observer.rewind(),
+        }
         self.slot_machines.iter_mut().map(|x| x.reset()).collect::<Vec<()>>();
     }
     pub fn play(&mut self, index: usize) -> Result<f64, String> {
+        self.play_vector(index).map(|v| v[0])
+    }
+    pub fn play_vector(&mut self, index: usize) -> Result<Vec<f64>, String> {
         let max_index = self.slot_machines.len();
         if let State::End = self.state {
             return Err("A game is not started. Please start a game.".to_string())
         }
         match self.slot_machines.get_mut(index) {
-            Some(slot) => {
-                let state = self.state.play();
+            Some(_) => {
+                let round = self.history.len();
+                self.slot_machines.iter_mut().for_each(|x| x.slot.advance(round));
+                let slot = &mut self.slot_machines[index];
+                self.state = self.state.play();
                 let reword = slot.play();
-                self.scores.push(reword);
+                self.scores.push(reword[0]);
+                self.history.push((index, reword[0]));
                 Ok(reword)
             }
             None => Err("Index out of range.".to_string())
         }
     }
+    pub fn observe(&mut self, index: usize) -> Result<f64, String> {
+        if let State::End = self.state {
+            return Err("A game is not started. Please start a game.".to_string())
+        }
+        match self.slot_machines.get(index) {
+            Some(machine) => Ok(machine.slot.peek(&mut self.observer)),
+            None => Err("Index out of range.".to_string())
+        }
+    }
     pub fn score(&self) -> f64 {
         self.scores.iter().sum()
     }
+    pub fn score_with(&self, objective: &Objective) -> f64 {
+        let rewards: Vec<f64> = self.history.iter().map(|(_, reward)| *reward).collect();
+        objective.evaluate(&rewards)
+    }
+    pub fn reset(&mut self) {
+        self.state = State::End;
+        self.scores.clear();
+        self.history.clear();
+        self.slot_machines.iter_mut().for_each(|x| x.clear());
+    }
+    pub fn finish(&mut self) {
+        self.state = State::End;
+    }
+    pub fn history(&self) -> &[(usize, f64)] {
+        &self.history
+    }
+    pub fn counterfactual_rewards(&self) -> Vec<Vec<f64>> {
+        self.slot_machines.iter().map(|machine| {
+            let mut slot = machine.slot.box_clone();
+            slot.initialize();
+            (0..self.history.len()).map(|round| {
+                slot.advance(round);
+                slot.play()
+            }).collect()
+        }).collect()
+    }
+    pub fn is_playing(&self) -> bool {
+        match self.state {
+            State::Playing { .. } => true,
+            State::End => false,
+        }
+    }
+    pub fn horizon(&self) -> Option<usize> {
+        self.horizon
+    }
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+    pub fn n_machines(&self) -> usize {
+        self.slot_machines.len()
+    }
     pub fn play_count(&self) -> usize {
         self.scores.len()
     }
@@ -203,4 +775,35 @@ impl Game {
         self.slot_machines.iter().map(|x| x.slot.profile())
         .collect::<Vec<f64>>()
     }
+    pub fn profile_vectors(&self) -> Vec<Vec<f64>> {
+        self.slot_machines.iter().map(|x| x.slot.profile_vector()).collect()
+    }
+    pub fn markov_arms(&self) -> Option<Vec<MarkovArm>> {
+        self.slot_machines.iter().map(|x| x.slot.markov().map(|(arm, _)| arm.clone())).collect()
+    }
+    pub fn markov_states(&self) -> Option<Vec<usize>> {
+        self.slot_machines.iter().map(|x| x.slot.markov().map(|(_, state)| state)).collect()
+    }
+    pub fn n_objectives(&self) -> usize {
+        self.slot_machines.first().map(|x| x.slot.profile_vector().len()).unwrap_or(0)
+    }
+    pub fn stored_profiles(&self, index: usize) -> Result<Vec<f64>, String> {
+        match self.slot_machines.get(index) {
+            Some(machine) => Ok(machine.repository.hist().iter().map(|x| x.profile()).collect()),
+            None => Err("Index out of range.".to_string())
+        }
+    }
+    pub fn swap_slot(&mut self, index: usize, nth: usize) -> Result<(), String> {
+        match self.slot_machines.get_mut(index) {
+            Some(machine) => machine.set_nth(nth),
+            None => Err("Index out of range.".to_string())
+        }
+    }
+    pub fn replace_slot(&mut self, index: usize, kind: &str, params: &SlotParams) -> Result<usize, String> {
+        let seed = self.master.gen();
+        let machine = self.slot_machines.get_mut(index).ok_or("Index out of range.".to_string())?;
+        let slot = machine.repository.gen(kind, params, self.source.generator(seed))?;
+        machine.set(slot);
+        Ok(machine.repository.hist().len() - 1)
+    }
 }
@@ -0,0 +1,58 @@
+use std::vec::Vec;
+use rand::RngCore;
+use serde::Serialize;
+use crate::agent::{self, argmax, Agent, Thompson, Ucb1};
+use crate::game::Game;
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Hint {
+    pub agent: String,
+    pub arm: usize,
+    pub basis: String,
+    pub values: Vec<Option<f64>>,
+    pub plays: usize,
+}
+
+fn replay(agent: &mut dyn Agent, game: &Game) {
+    agent.reset();
+    for (arm, reward) in game.history() {
+        agent.update(*arm, *reward);
+    }
+}
+
+fn finite(values: Vec<f64>) -> Vec<Option<f64>> {
+    values.into_iter().map(|v| if v.is_finite() { Some(v) } else { None }).collect()
+}
+
+pub fn hint(game: &mut Game, name: &str, rng: &mut dyn RngCore) -> Result<Hint, String> {
+    if !game.hints_enabled() {
+        return Err("Hints are disabled for this game.".to_string())
+    }
+    if !game.is_playing() {
+        return Err("A game is not started. Please start a game.".to_string())
+    }
+    let n = game.n_machines();
+    let (arm, basis, values) = match name {
+        "ucb1" => {
+            let mut ucb = Ucb1::new(n);
+            replay(&mut ucb, game);
+            let indices = ucb.indices();
+            (argmax(&indices), "ucb_index", indices)
+        }
+        "thompson" => {
+            let mut thompson = Thompson::new(n);
+            replay(&mut thompson, game);
+            let samples = thompson.sample(rng);
+            (argmax(&samples), "posterior_sample", samples)
+        }
+        _ => {
+            let mut agent = agent::by_name(name, n)?;
+            replay(agent.as_mut(), game);
+            let probs = agent.probs(rng);
+            (argmax(&probs), "probability", probs)
+        }
+    };
+    game.record_hint();
+    Ok(Hint { agent: name.to_string(), arm, basis: basis.to_string(), values: finite(values), plays: game.history().len() })
+}
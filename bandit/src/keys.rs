@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use rand::random;
+use serde::Serialize;
+
+
+const MINUTE: Duration = Duration::from_secs(60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Limits {
+    pub pulls_per_minute: usize,
+    pub games_per_day: usize,
+    pub keys_per_day: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits { pulls_per_minute: 600, games_per_day: 100, keys_per_day: 5 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charge {
+    Pull,
+    Game,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "error", rename_all = "kebab-case")]
+pub enum Denied {
+    MissingKey,
+    UnknownKey,
+    RateLimited { retry_after: u64 },
+    QuotaExceeded { retry_after: u64 },
+}
+
+impl Denied {
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Denied::RateLimited { retry_after } | Denied::QuotaExceeded { retry_after } => Some(*retry_after),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for Denied {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Denied::MissingKey => write!(f, "An API key is required. Register one at /keys/register/<name>."),
+            Denied::UnknownKey => write!(f, "Unknown API key."),
+            Denied::RateLimited { retry_after } => write!(f, "Too many pulls. Retry in {} seconds.", retry_after),
+            Denied::QuotaExceeded { retry_after } => write!(f, "Daily quota exceeded. Retry in {} seconds.", retry_after),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    start: Instant,
+    count: usize,
+}
+
+impl Window {
+    fn new(now: Instant) -> Window {
+        Window { start: now, count: 0 }
+    }
+    fn charge(&mut self, now: Instant, length: Duration, limit: usize) -> Result<(), u64> {
+        if now.duration_since(self.start) >= length {
+            *self = Window::new(now);
+        }
+        if self.count >= limit {
+            let left = length - now.duration_since(self.start);
+            return Err(left.as_secs() + (left.subsec_nanos() > 0) as u64)
+        }
+        self.count += 1;
+        Ok(())
+    }
+    fn refund(&mut self, now: Instant, length: Duration) {
+        if now.duration_since(self.start) < length {
+            self.count = self.count.saturating_sub(1);
+        }
+    }
+    fn current(&self, now: Instant, length: Duration) -> usize {
+        if now.duration_since(self.start) >= length { 0 } else { self.count }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Usage {
+    pub name: String,
+    pub pulls: usize,
+    pub games: usize,
+    pub pulls_this_minute: usize,
+    pub games_today: usize,
+    pub limits: Limits,
+}
+
+struct Account {
+    name: String,
+    pulls: Window,
+    games: Window,
+    total_pulls: usize,
+    total_games: usize,
+}
+
+pub struct KeyStore {
+    limits: Limits,
+    required: bool,
+    accounts: Mutex<HashMap<String, Account>>,
+    registrations: Mutex<HashMap<Option<IpAddr>, Window>>,
+}
+
+impl KeyStore {
+    pub fn new(limits: Limits, required: bool) -> KeyStore {
+        KeyStore { limits, required, accounts: Mutex::new(HashMap::new()), registrations: Mutex::new(HashMap::new()) }
+    }
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+    pub fn register(&self, name: &str, client: Option<IpAddr>, now: Instant) -> Result<String, Denied> {
+        self.registrations.lock().unwrap()
+            .entry(client).or_insert_with(|| Window::new(now))
+            .charge(now, DAY, self.limits.keys_per_day)
+            .map_err(|retry_after| Denied::QuotaExceeded { retry_after })?;
+        let key = format!("{:032x}", random::<u128>());
+        let account = Account { name: name.to_string(), pulls: Window::new(now), games: Window::new(now), total_pulls: 0, total_games: 0 };
+        self.accounts.lock().unwrap().insert(key.clone(), account);
+        Ok(key)
+    }
+    pub fn charge(&self, key: Option<&str>, charge: Charge, now: Instant) -> Result<(), Denied> {
+        if !self.required {
+            return Ok(())
+        }
+        let key = key.ok_or(Denied::MissingKey)?;
+        let mut accounts = self.accounts.lock().unwrap();
+        let account = accounts.get_mut(key).ok_or(Denied::UnknownKey)?;
+        match charge {
+            Charge::Pull => {
+                account.pulls.charge(now, MINUTE, self.limits.pulls_per_minute)
+                    .map_err(|retry_after| Denied::RateLimited { retry_after })?;
+                account.total_pulls += 1;
+            }
+            Charge::Game => {
+                account.games.charge(now, DAY, self.limits.games_per_day)
+                    .map_err(|retry_after| Denied::QuotaExceeded { retry_after })?;
+                account.total_games += 1;
+            }
+        }
+        Ok(())
+    }
+    pub fn refund(&self, key: Option<&str>, charge: Charge, now: Instant) {
+        if !self.required {
+            return
+        }
+        let mut accounts = self.accounts.lock().unwrap();
+        let account = match key.and_then(|key| accounts.get_mut(key)) {
+            Some(account) => account,
+            None => return
+        };
+        match charge {
+            Charge::Pull => {
+                account.pulls.refund(now, MINUTE);
+                account.total_pulls = account.total_pulls.saturating_sub(1);
+            }
+            Charge::Game => {
+                account.games.refund(now, DAY);
+                account.total_games = account.total_games.saturating_sub(1);
+            }
+        }
+    }
+    pub fn usage(&self, key: &str, now: Instant) -> Result<Usage, Denied> {
+        let accounts = self.accounts.lock().unwrap();
+        let account = accounts.get(key).ok_or(Denied::UnknownKey)?;
+        Ok(Usage {
+            name: account.name.clone(),
+            pulls: account.total_pulls,
+            games: account.total_games,
+            pulls_this_minute: account.pulls.current(now, MINUTE),
+            games_today: account.games.current(now, DAY),
+            limits: self.limits,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits { pulls_per_minute: 2, games_per_day: 1, keys_per_day: 5 }
+    }
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn the_limit_reports_when_to_retry() {
+        let start = Instant::now();
+        let keys = KeyStore::new(limits(), true);
+        let key = keys.register("a", None, start).unwrap();
+        keys.charge(Some(&key), Charge::Pull, start).unwrap();
+        keys.charge(Some(&key), Charge::Pull, start + secs(10)).unwrap();
+        assert_eq!(keys.charge(Some(&key), Charge::Pull, start + secs(20)), Err(Denied::RateLimited { retry_after: 40 }));
+        keys.charge(Some(&key), Charge::Game, start).unwrap();
+        assert_eq!(keys.charge(Some(&key), Charge::Game, start + secs(3600)), Err(Denied::QuotaExceeded { retry_after: 86400 - 3600 }));
+    }
+
+    #[test]
+    fn the_window_resets() {
+        let start = Instant::now();
+        let keys = KeyStore::new(limits(), true);
+        let key = keys.register("a", None, start).unwrap();
+        keys.charge(Some(&key), Charge::Pull, start).unwrap();
+        keys.charge(Some(&key), Charge::Pull, start).unwrap();
+        assert!(keys.charge(Some(&key), Charge::Pull, start + secs(59)).is_err());
+        keys.charge(Some(&key), Charge::Pull, start + secs(60)).unwrap();
+        assert_eq!(keys.usage(&key, start + secs(60)).unwrap().pulls_this_minute, 1);
+        assert_eq!(keys.usage(&key, start + secs(60)).unwrap().pulls, 3);
+    }
+
+    #[test]
+    fn a_refund_restores_capacity_in_the_current_window() {
+        let start = Instant::now();
+        let keys = KeyStore::new(limits(), true);
+        let key = keys.register("a", None, start).unwrap();
+        keys.charge(Some(&key), Charge::Pull, start).unwrap();
+        keys.charge(Some(&key), Charge::Pull, start).unwrap();
+        keys.refund(Some(&key), Charge::Pull, start + secs(30));
+        keys.charge(Some(&key), Charge::Pull, start + secs(30)).unwrap();
+        assert!(keys.charge(Some(&key), Charge::Pull, start + secs(30)).is_err());
+        assert_eq!(keys.usage(&key, start + secs(30)).unwrap().pulls, 2);
+    }
+
+    #[test]
+    fn a_refund_outside_the_window_is_ignored() {
+        let start = Instant::now();
+        let mut window = Window { start, count: 2 };
+        window.refund(start + MINUTE, MINUTE);
+        assert_eq!(window.count, 2);
+        let mut fresh = Window::new(start);
+        fresh.refund(start, MINUTE);
+        assert_eq!(fresh.count, 0);
+    }
+
+    #[test]
+    fn every_charge_passes_when_keys_are_optional() {
+        let now = Instant::now();
+        let keys = KeyStore::new(limits(), false);
+        for _ in 0..10 {
+            assert_eq!(keys.charge(None, Charge::Pull, now), Ok(()));
+            assert_eq!(keys.charge(Some("unknown"), Charge::Game, now), Ok(()));
+        }
+    }
+}
@@ -3,3 +3,25 @@
 
 pub mod game;
 pub mod app;
+pub mod agent;
+pub mod offline;
+pub mod simulation;
+pub mod plot;
+pub mod spec;
+pub mod registry;
+pub mod env;
+pub mod rng;
+pub mod analysis;
+pub mod robust;
+pub mod risk;
+pub mod pareto;
+pub mod continuous;
+pub mod markov;
+pub mod feedback;
+pub mod room;
+pub mod hint;
+pub mod tcp;
+pub mod metrics;
+pub mod audit;
+pub mod keys;
+pub mod bounds;
@@ -0,0 +1,248 @@
+use std::any::Any;
+use std::vec::Vec;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::agent::{argmax, sample_index};
+use crate::game::{Game, Replayable};
+use crate::offline::solve;
+use crate::rng::{RandomSource, ReplayRng, SlotRng};
+
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkovArm {
+    pub transitions: Vec<Vec<f64>>,
+    pub rewards: Vec<f64>,
+    #[serde(default)]
+    pub initial: usize,
+}
+
+impl MarkovArm {
+    pub fn n_states(&self) -> usize {
+        self.rewards.len()
+    }
+    pub fn validate(&self) -> Result<(), String> {
+        let n = self.n_states();
+        if n == 0 {
+            return Err("a markov arm needs at least one state".to_string())
+        }
+        if self.transitions.len() != n {
+            return Err(format!("expected {} transition rows, got {}", n, self.transitions.len()))
+        }
+        for (i, row) in self.transitions.iter().enumerate() {
+            if row.len() != n {
+                return Err(format!("transitions[{}]: expected {} entries, got {}", i, n, row.len()))
+            }
+            if row.iter().any(|p| p.is_nan() || *p < 0.) || (row.iter().sum::<f64>() - 1.).abs() > 1e-9 {
+                return Err(format!("transitions[{}]: probabilities must be non-negative and sum to 1", i))
+            }
+        }
+        if self.rewards.iter().any(|r| !r.is_finite()) {
+            return Err("rewards must be finite".to_string())
+        }
+        if self.initial >= n {
+            return Err(format!("initial state {} is out of range", self.initial))
+        }
+        Ok(())
+    }
+    fn random(n_states: usize, rng: &mut dyn SlotRng) -> MarkovArm {
+        let transitions = (0..n_states).map(|_| {
+            let row: Vec<f64> = (0..n_states).map(|_| rng.gen::<f64>()).collect();
+            let total: f64 = row.iter().sum();
+            row.into_iter().map(|p| p / total).collect()
+        }).collect();
+        let rewards = (0..n_states).map(|_| rng.gen()).collect();
+        MarkovArm { transitions, rewards, initial: 0 }
+    }
+}
+
+
+#[derive(Clone)]
+pub(crate) struct MarkovSlot {
+    arm: MarkovArm,
+    state: usize,
+    rng: ReplayRng,
+}
+
+impl Replayable for MarkovSlot {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn box_clone(&self) -> Box<dyn Replayable + Send> {
+        Box::new(self.clone())
+    }
+    fn initialize(&mut self) {
+        self.state = self.arm.initial;
+        self.rng.rewind()
+    }
+    fn reseed(&mut self, source: &dyn RandomSource, seed: u64) {
+        self.rng = ReplayRng::new(source.generator(seed))
+    }
+    fn play(&mut self) -> f64 {
+        let reward = self.arm.rewards[self.state];
+        self.state = sample_index(&self.arm.transitions[self.state], &mut self.rng);
+        reward
+    }
+    fn peek(&self, _rng: &mut dyn SlotRng) -> f64 {
+        self.arm.rewards[self.state]
+    }
+    fn profile(&self) -> f64 {
+        self.arm.rewards[self.state]
+    }
+    fn markov(&self) -> Option<(&MarkovArm, usize)> {
+        Some((&self.arm, self.state))
+    }
+}
+
+impl MarkovSlot {
+    pub(crate) fn new(arm: MarkovArm, rng: Box<dyn SlotRng>) -> Result<MarkovSlot, String> {
+        arm.validate()?;
+        Ok(MarkovSlot { state: arm.initial, arm, rng: ReplayRng::new(rng) })
+    }
+    pub(crate) fn from_rng(rng: &mut dyn SlotRng, source: &dyn RandomSource) -> MarkovSlot {
+        let seed: u64 = rng.gen();
+        let arm = MarkovArm::random(3, rng);
+        MarkovSlot { state: arm.initial, arm, rng: ReplayRng::new(source.generator(seed)) }
+    }
+}
+
+
+pub fn gittins_indices(arm: &MarkovArm, discount: f64) -> Vec<f64> {
+    let n = arm.n_states();
+    let mut indices = vec![0.; n];
+    let mut continuation: Vec<usize> = vec![];
+    while continuation.len() < n {
+        let m = continuation.len();
+        let a: Vec<Vec<f64>> = (0..m).map(|i| (0..m).map(|j| {
+            (i == j) as u32 as f64 - discount * arm.transitions[continuation[i]][continuation[j]]
+        }).collect()).collect();
+        let value = solve(a.clone(), continuation.iter().map(|s| arm.rewards[*s]).collect());
+        let time = solve(a, vec![1.; m]);
+        let ratios: Vec<f64> = (0..n).map(|s| {
+            if continuation.contains(&s) {
+                return f64::NEG_INFINITY
+            }
+            let row = &arm.transitions[s];
+            let reward = arm.rewards[s] + discount * (0..m).map(|j| row[continuation[j]] * value[j]).sum::<f64>();
+            let duration = 1. + discount * (0..m).map(|j| row[continuation[j]] * time[j]).sum::<f64>();
+            reward / duration
+        }).collect();
+        let next = argmax(&ratios);
+        indices[next] = ratios[next];
+        continuation.push(next);
+    }
+    indices
+}
+
+
+pub trait IndexPolicy {
+    fn name(&self) -> String;
+    fn select(&self, states: &[usize]) -> usize;
+}
+
+pub struct GittinsPolicy {
+    indices: Vec<Vec<f64>>,
+}
+
+impl GittinsPolicy {
+    pub fn new(arms: &[MarkovArm], discount: f64) -> GittinsPolicy {
+        GittinsPolicy { indices: arms.iter().map(|arm| gittins_indices(arm, discount)).collect() }
+    }
+    pub fn indices(&self) -> &[Vec<f64>] {
+        &self.indices
+    }
+}
+
+impl IndexPolicy for GittinsPolicy {
+    fn name(&self) -> String {
+        "gittins".to_string()
+    }
+    fn select(&self, states: &[usize]) -> usize {
+        let current: Vec<f64> = states.iter().enumerate().map(|(arm, s)| self.indices[arm][*s]).collect();
+        argmax(&current)
+    }
+}
+
+pub struct MyopicPolicy {
+    rewards: Vec<Vec<f64>>,
+}
+
+impl MyopicPolicy {
+    pub fn new(arms: &[MarkovArm]) -> MyopicPolicy {
+        MyopicPolicy { rewards: arms.iter().map(|arm| arm.rewards.clone()).collect() }
+    }
+}
+
+impl IndexPolicy for MyopicPolicy {
+    fn name(&self) -> String {
+        "myopic".to_string()
+    }
+    fn select(&self, states: &[usize]) -> usize {
+        let current: Vec<f64> = states.iter().enumerate().map(|(arm, s)| self.rewards[arm][*s]).collect();
+        argmax(&current)
+    }
+}
+
+pub fn run(policy: &dyn IndexPolicy, game: &mut Game, horizon: usize, discount: f64) -> Result<f64, String> {
+    game.start(Some(horizon));
+    let mut total = 0.;
+    let mut weight = 1.;
+    for _ in 0..horizon {
+        let states = game.markov_states().ok_or("The game has non-markov arms.".to_string())?;
+        let reward = game.play(policy.select(&states))?;
+        total += weight * reward;
+        weight *= discount;
+    }
+    Ok(total)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::game::EpisodeMode;
+    use crate::spec::GameSpec;
+    use super::*;
+
+    const SPEC: &str = r#"
+horizon = 200
+seed = 3
+
+[[arms]]
+type = "markov"
+markov = { transitions = [[1.0]], rewards = [0.5] }
+
+[[arms]]
+type = "markov"
+markov = { transitions = [[0.6, 0.4, 0.0], [0.0, 0.7, 0.3], [0.0, 0.0, 1.0]], rewards = [0.1, 0.2, 1.0] }
+"#;
+
+    #[test]
+    fn single_state_index_is_its_reward() {
+        let arm = MarkovArm { transitions: vec![vec![1.]], rewards: vec![0.3], initial: 0 };
+        assert_eq!(gittins_indices(&arm, 0.9), vec![0.3]);
+    }
+
+    #[test]
+    fn two_state_index_matches_the_closed_form() {
+        // State 1 pays 0 and moves to the absorbing state 0, which pays 1, so
+        // stopping never helps: the index of state 1 is (b / (1 - b)) / (1 / (1 - b)) = b.
+        let arm = MarkovArm { transitions: vec![vec![1., 0.], vec![1., 0.]], rewards: vec![1., 0.], initial: 1 };
+        let discount = 0.8;
+        let indices = gittins_indices(&arm, discount);
+        assert!((indices[0] - 1.).abs() < 1e-12);
+        assert!((indices[1] - discount).abs() < 1e-12);
+    }
+
+    #[test]
+    fn gittins_beats_myopic() {
+        let spec = GameSpec::from_toml(SPEC).unwrap();
+        let mut game = Game::from_spec(&spec).unwrap();
+        game.set_mode(EpisodeMode::Fresh);
+        let arms = game.markov_arms().unwrap();
+        let discount = 0.95;
+        let policies: Vec<Box<dyn IndexPolicy>> = vec![Box::new(GittinsPolicy::new(&arms, discount)), Box::new(MyopicPolicy::new(&arms))];
+        let totals: Vec<f64> = policies.iter().map(|policy| {
+            (0..20).map(|_| run(policy.as_ref(), &mut game, 200, discount).unwrap()).sum::<f64>() / 20.
+        }).collect();
+        assert!(totals[0] > totals[1], "gittins {} vs myopic {}", totals[0], totals[1]);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+
+const BUCKETS: [f64; 10] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.5, 1.];
+const WINDOW: usize = 60;
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if self.counts.is_empty() {
+            self.counts = vec![0; BUCKETS.len()];
+        }
+        BUCKETS.iter().zip(self.counts.iter_mut()).filter(|(le, _)| seconds <= **le).for_each(|(_, c)| *c += 1);
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    requests: BTreeMap<(String, u16), u64>,
+    errors: BTreeMap<String, u64>,
+    latency: BTreeMap<String, Histogram>,
+    pulls: u64,
+    window: Vec<(u64, u64)>,
+}
+
+pub struct Metrics {
+    started: Instant,
+    inner: Mutex<Inner>,
+}
+
+impl Default for Metrics {
+    fn default() -> Metrics {
+        Metrics::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics { started: Instant::now(), inner: Mutex::new(Inner::default()) }
+    }
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+    fn second(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+    pub fn record(&self, route: &str, status: u16, elapsed: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner.requests.entry((route.to_string(), status)).or_insert(0) += 1;
+        if status >= 400 {
+            *inner.errors.entry(route.to_string()).or_insert(0) += 1;
+        }
+        inner.latency.entry(route.to_string()).or_default().observe(elapsed.as_secs_f64());
+    }
+    pub fn pull(&self) {
+        let now = self.second();
+        let mut inner = self.inner.lock().unwrap();
+        inner.pulls += 1;
+        if inner.window.is_empty() {
+            inner.window = vec![(0, 0); WINDOW];
+        }
+        let slot = &mut inner.window[now as usize % WINDOW];
+        if slot.0 != now {
+            *slot = (now, 0);
+        }
+        slot.1 += 1;
+    }
+    pub fn pulls(&self) -> u64 {
+        self.inner.lock().unwrap().pulls
+    }
+    pub fn pulls_per_second(&self) -> f64 {
+        let now = self.second();
+        let inner = self.inner.lock().unwrap();
+        let recent: u64 = inner.window.iter().filter(|(s, _)| *s < now && now - *s <= WINDOW as u64).map(|(_, n)| n).sum();
+        recent as f64 / (now.min(WINDOW as u64).max(1)) as f64
+    }
+    pub fn render(&self, sessions: &[(&str, usize)]) -> String {
+        let pulls_per_second = self.pulls_per_second();
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+        writeln!(out, "# HELP bandit_uptime_seconds Seconds since the server started.").unwrap();
+        writeln!(out, "# TYPE bandit_uptime_seconds gauge").unwrap();
+        writeln!(out, "bandit_uptime_seconds {}", self.uptime().as_secs_f64()).unwrap();
+        writeln!(out, "# HELP bandit_active_sessions Games currently being played.").unwrap();
+        writeln!(out, "# TYPE bandit_active_sessions gauge").unwrap();
+        for (kind, n) in sessions {
+            writeln!(out, "bandit_active_sessions{{kind=\"{}\"}} {}", kind, n).unwrap();
+        }
+        writeln!(out, "# HELP bandit_pulls_total Arms pulled successfully.").unwrap();
+        writeln!(out, "# TYPE bandit_pulls_total counter").unwrap();
+        writeln!(out, "bandit_pulls_total {}", inner.pulls).unwrap();
+        writeln!(out, "# HELP bandit_pulls_per_second Pulls per second over the last {} complete seconds.", WINDOW).unwrap();
+        writeln!(out, "# TYPE bandit_pulls_per_second gauge").unwrap();
+        writeln!(out, "bandit_pulls_per_second {}", pulls_per_second).unwrap();
+        writeln!(out, "# HELP bandit_http_requests_total HTTP requests by route and status.").unwrap();
+        writeln!(out, "# TYPE bandit_http_requests_total counter").unwrap();
+        for ((route, status), n) in inner.requests.iter() {
+            writeln!(out, "bandit_http_requests_total{{route=\"{}\",status=\"{}\"}} {}", escape(route), status, n).unwrap();
+        }
+        writeln!(out, "# HELP bandit_http_errors_total HTTP responses with status 400 or above.").unwrap();
+        writeln!(out, "# TYPE bandit_http_errors_total counter").unwrap();
+        for (route, n) in inner.errors.iter() {
+            writeln!(out, "bandit_http_errors_total{{route=\"{}\"}} {}", escape(route), n).unwrap();
+        }
+        writeln!(out, "# HELP bandit_http_request_duration_seconds HTTP request latency by route.").unwrap();
+        writeln!(out, "# TYPE bandit_http_request_duration_seconds histogram").unwrap();
+        for (route, h) in inner.latency.iter() {
+            let route = escape(route);
+            for (le, c) in BUCKETS.iter().zip(h.counts.iter()) {
+                writeln!(out, "bandit_http_request_duration_seconds_bucket{{route=\"{}\",le=\"{}\"}} {}", route, le, c).unwrap();
+            }
+            writeln!(out, "bandit_http_request_duration_seconds_bucket{{route=\"{}\",le=\"+Inf\"}} {}", route, h.count).unwrap();
+            writeln!(out, "bandit_http_request_duration_seconds_sum{{route=\"{}\"}} {}", route, h.sum).unwrap();
+            writeln!(out, "bandit_http_request_duration_seconds_count{{route=\"{}\"}} {}", route, h.count).unwrap();
+        }
+        out
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,229 @@
+use std::io::{BufRead, Write};
+use std::vec::Vec;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+use crate::agent::{Agent, sample_index};
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedPlay {
+    #[serde(default)]
+    pub context: Vec<f64>,
+    pub action: usize,
+    pub reward: f64,
+    pub propensity: f64,
+}
+
+pub fn read_log<R: BufRead>(reader: R) -> Result<Vec<LoggedPlay>, String> {
+    let mut log: Vec<LoggedPlay> = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue
+        }
+        let play: LoggedPlay = serde_json::from_str(&line)
+            .map_err(|e| format!("line {}: {}", i + 1, e))?;
+        if !(play.propensity > 0. && play.propensity <= 1.) {
+            return Err(format!("line {}: propensity must be in (0, 1]", i + 1))
+        }
+        if !play.reward.is_finite() || play.context.iter().any(|x| !x.is_finite()) {
+            return Err(format!("line {}: reward and context must be finite", i + 1))
+        }
+        if let Some(first) = log.first() {
+            if play.context.len() != first.context.len() {
+                return Err(format!("line {}: context has {} features, expected {}", i + 1, play.context.len(), first.context.len()))
+            }
+        }
+        log.push(play);
+    }
+    Ok(log)
+}
+
+pub fn write_log<W: Write>(mut writer: W, log: &[LoggedPlay]) -> Result<(), String> {
+    for play in log {
+        let line = serde_json::to_string(play).map_err(|e| e.to_string())?;
+        writeln!(writer, "{}", line).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+
+pub trait Policy {
+    fn probs(&mut self, context: &[f64], rng: &mut dyn RngCore) -> Vec<f64>;
+    fn update(&mut self, context: &[f64], action: usize, reward: f64);
+}
+
+/// Agents are context-free: the context of each logged row is ignored.
+impl<A: Agent + ?Sized> Policy for A {
+    fn probs(&mut self, _context: &[f64], rng: &mut dyn RngCore) -> Vec<f64> {
+        Agent::probs(self, rng)
+    }
+    fn update(&mut self, _context: &[f64], action: usize, reward: f64) {
+        Agent::update(self, action, reward)
+    }
+}
+
+
+pub trait RewardModel {
+    fn fit(&mut self, log: &[LoggedPlay]);
+    fn predict(&self, context: &[f64], action: usize) -> f64;
+}
+
+pub struct RidgeModel {
+    lambda: f64,
+    weights: Vec<Vec<f64>>,
+}
+
+impl RidgeModel {
+    pub fn new(n_arms: usize, lambda: f64) -> RidgeModel {
+        RidgeModel { lambda, weights: vec![vec![]; n_arms] }
+    }
+}
+
+fn features(context: &[f64]) -> Vec<f64> {
+    let mut x = Vec::with_capacity(context.len() + 1);
+    x.push(1.);
+    x.extend_from_slice(context);
+    x
+}
+
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs())).unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        if a[col][col].abs() < 1e-12 {
+            continue
+        }
+        for row in col + 1..n {
+            let f = a[row][col] / a[col][col];
+            let (upper, lower) = a.split_at_mut(row);
+            for (x, y) in lower[0][col..].iter_mut().zip(upper[col][col..].iter()) {
+                *x -= f * y;
+            }
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        if a[row][row].abs() < 1e-12 {
+            continue
+        }
+        let s: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - s) / a[row][row];
+    }
+    x
+}
+
+impl RewardModel for RidgeModel {
+    fn fit(&mut self, log: &[LoggedPlay]) {
+        let dim = log.first().map(|p| p.context.len() + 1).unwrap_or(1);
+        for (arm, w) in self.weights.iter_mut().enumerate() {
+            let mut a = vec![vec![0.; dim]; dim];
+            let mut b = vec![0.; dim];
+            for (i, row) in a.iter_mut().enumerate() {
+                row[i] = self.lambda;
+            }
+            for play in log.iter().filter(|p| p.action == arm && p.context.len() + 1 == dim) {
+                let x = features(&play.context);
+                for i in 0..dim {
+                    for j in 0..dim {
+                        a[i][j] += x[i] * x[j];
+                    }
+                    b[i] += x[i] * play.reward;
+                }
+            }
+            *w = solve(a, b);
+        }
+    }
+    fn predict(&self, context: &[f64], action: usize) -> f64 {
+        match self.weights.get(action) {
+            Some(w) => features(context).iter().zip(w.iter()).map(|(x, w)| x * w).sum(),
+            None => 0.
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub variance: f64,
+    pub n: usize,
+}
+
+impl Estimate {
+    fn from_terms(terms: &[f64]) -> Estimate {
+        let n = terms.len();
+        let mean = terms.iter().sum::<f64>() / n.max(1) as f64;
+        let var = if n > 1 {
+            terms.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else { 0. };
+        Estimate { value: mean, variance: var / n.max(1) as f64, n }
+    }
+    pub fn std_error(&self) -> f64 {
+        self.variance.sqrt()
+    }
+    pub fn interval(&self, z: f64) -> (f64, f64) {
+        (self.value - z * self.std_error(), self.value + z * self.std_error())
+    }
+}
+
+/// Replay evaluation: each row is scored with the policy's current probs, then
+/// the policy draws an action and is updated only when it matches the logged
+/// one, so a learning agent only sees feedback it would have got online.
+fn replay<P, F>(policy: &mut P, log: &[LoggedPlay], rng: &mut dyn RngCore, mut f: F) -> Result<Vec<f64>, String>
+    where
+        P: Policy + ?Sized,
+        F: FnMut(&LoggedPlay, &[f64]) -> f64
+{
+    let mut terms = Vec::with_capacity(log.len());
+    for (i, play) in log.iter().enumerate() {
+        let probs = policy.probs(&play.context, rng);
+        if play.action >= probs.len() {
+            return Err(format!("entry {}: action {} out of range", i, play.action))
+        }
+        terms.push(f(play, &probs));
+        if sample_index(&probs, rng) == play.action {
+            policy.update(&play.context, play.action, play.reward);
+        }
+    }
+    Ok(terms)
+}
+
+pub fn ips<P: Policy + ?Sized>(policy: &mut P, log: &[LoggedPlay], rng: &mut dyn RngCore) -> Result<Estimate, String> {
+    let terms = replay(policy, log, rng, |play, probs| {
+        probs[play.action] / play.propensity * play.reward
+    })?;
+    Ok(Estimate::from_terms(&terms))
+}
+
+pub fn snips<P: Policy + ?Sized>(policy: &mut P, log: &[LoggedPlay], rng: &mut dyn RngCore) -> Result<Estimate, String> {
+    let weights = replay(policy, log, rng, |play, probs| probs[play.action] / play.propensity)?;
+    let total: f64 = weights.iter().sum();
+    if total <= 0. {
+        return Ok(Estimate { value: 0., variance: 0., n: log.len() })
+    }
+    let value = weights.iter().zip(log.iter()).map(|(w, p)| w * p.reward).sum::<f64>() / total;
+    let variance = weights.iter().zip(log.iter())
+        .map(|(w, p)| (w * (p.reward - value)).powi(2))
+        .sum::<f64>() / total.powi(2);
+    Ok(Estimate { value, variance, n: log.len() })
+}
+
+pub fn direct_method<P: Policy + ?Sized>(policy: &mut P, log: &[LoggedPlay], model: &dyn RewardModel, rng: &mut dyn RngCore) -> Result<Estimate, String> {
+    let terms = replay(policy, log, rng, |play, probs| {
+        probs.iter().enumerate().map(|(a, p)| p * model.predict(&play.context, a)).sum()
+    })?;
+    Ok(Estimate::from_terms(&terms))
+}
+
+pub fn doubly_robust<P: Policy + ?Sized>(policy: &mut P, log: &[LoggedPlay], model: &dyn RewardModel, rng: &mut dyn RngCore) -> Result<Estimate, String> {
+    let terms = replay(policy, log, rng, |play, probs| {
+        let dm: f64 = probs.iter().enumerate().map(|(a, p)| p * model.predict(&play.context, a)).sum();
+        let residual = play.reward - model.predict(&play.context, play.action);
+        dm + probs[play.action] / play.propensity * residual
+    })?;
+    Ok(Estimate::from_terms(&terms))
+}
@@ -0,0 +1,192 @@
+use std::vec::Vec;
+use rand::{Rng, RngCore};
+use crate::agent::{argmax, one_hot, Agent};
+
+
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b.iter()).all(|(x, y)| x >= y) && a.iter().zip(b.iter()).any(|(x, y)| x > y)
+}
+
+pub fn pareto_front(vectors: &[Vec<f64>]) -> Vec<usize> {
+    (0..vectors.len())
+        .filter(|i| !vectors.iter().any(|v| dominates(v, &vectors[*i])))
+        .collect()
+}
+
+pub fn pareto_gap(vectors: &[Vec<f64>], arm: usize) -> f64 {
+    pareto_front(vectors).into_iter()
+        .map(|j| {
+            vectors[j].iter().zip(vectors[arm].iter())
+                .map(|(best, x)| best - x)
+                .fold(f64::INFINITY, f64::min)
+                .max(0.)
+        })
+        .fold(0., f64::max)
+}
+
+pub fn pareto_gaps(vectors: &[Vec<f64>]) -> Vec<f64> {
+    (0..vectors.len()).map(|i| pareto_gap(vectors, i)).collect()
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct VectorStats {
+    pub counts: Vec<usize>,
+    pub sums: Vec<Vec<f64>>,
+}
+
+impl VectorStats {
+    pub fn new(n_arms: usize) -> VectorStats {
+        VectorStats { counts: vec![0; n_arms], sums: vec![vec![]; n_arms] }
+    }
+    pub fn n_arms(&self) -> usize {
+        self.counts.len()
+    }
+    pub fn dimension(&self) -> usize {
+        self.sums.iter().map(|s| s.len()).max().unwrap_or(0)
+    }
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+    pub fn mean(&self, arm: usize) -> Vec<f64> {
+        let n = self.counts[arm].max(1) as f64;
+        let mut mean: Vec<f64> = self.sums[arm].iter().map(|s| s / n).collect();
+        mean.resize(self.dimension(), 0.);
+        mean
+    }
+    pub fn unplayed(&self) -> Option<usize> {
+        self.counts.iter().position(|c| *c == 0)
+    }
+    pub fn update(&mut self, arm: usize, reward: &[f64]) {
+        self.counts[arm] += 1;
+        let sums = &mut self.sums[arm];
+        if sums.len() < reward.len() {
+            sums.resize(reward.len(), 0.);
+        }
+        sums.iter_mut().zip(reward.iter()).for_each(|(s, r)| *s += r);
+    }
+    pub fn reset(&mut self) {
+        *self = VectorStats::new(self.n_arms());
+    }
+}
+
+
+pub struct ParetoUcb {
+    stats: VectorStats,
+}
+
+impl ParetoUcb {
+    pub fn new(n_arms: usize) -> ParetoUcb {
+        ParetoUcb { stats: VectorStats::new(n_arms) }
+    }
+    pub fn indices(&self) -> Vec<Vec<f64>> {
+        let n_arms = self.stats.n_arms() as f64;
+        let dimension = self.stats.dimension().max(1) as f64;
+        let total = self.stats.total().max(1) as f64;
+        (0..self.stats.n_arms()).map(|i| {
+            let n = self.stats.counts[i] as f64;
+            let bonus = (2. * (total * dimension.powf(0.25) * n_arms).ln() / n).sqrt();
+            self.stats.mean(i).into_iter().map(|m| m + bonus).collect()
+        }).collect()
+    }
+}
+
+impl Agent for ParetoUcb {
+    fn name(&self) -> String {
+        "pareto-ucb".to_string()
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        let mut probs = vec![0.; self.stats.n_arms()];
+        if let Some(arm) = self.stats.unplayed() {
+            probs[arm] = 1.;
+            return probs
+        }
+        let front = pareto_front(&self.indices());
+        front.iter().for_each(|i| probs[*i] = 1. / front.len() as f64);
+        probs
+    }
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        if let Some(arm) = self.stats.unplayed() {
+            return arm
+        }
+        let front = pareto_front(&self.indices());
+        front[rng.gen_range(0, front.len())]
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.stats.update(arm, &[reward])
+    }
+    fn update_vector(&mut self, arm: usize, reward: &[f64]) {
+        self.stats.update(arm, reward)
+    }
+    fn reset(&mut self) {
+        self.stats.reset()
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scalarization {
+    Linear(Vec<f64>),
+    Chebyshev(Vec<f64>),
+}
+
+impl Scalarization {
+    fn weights(weights: &[f64], dimension: usize) -> Vec<f64> {
+        if weights.is_empty() {
+            vec![1. / dimension.max(1) as f64; dimension]
+        } else {
+            weights.to_vec()
+        }
+    }
+    pub fn apply(&self, values: &[f64]) -> f64 {
+        match self {
+            Scalarization::Linear(weights) => Scalarization::weights(weights, values.len()).iter()
+                .zip(values.iter()).map(|(w, x)| w * x).sum(),
+            Scalarization::Chebyshev(weights) => Scalarization::weights(weights, values.len()).iter()
+                .zip(values.iter()).map(|(w, x)| w * x).fold(f64::INFINITY, f64::min),
+        }
+    }
+}
+
+
+pub struct ScalarizedUcb {
+    scalarization: Scalarization,
+    stats: VectorStats,
+}
+
+impl ScalarizedUcb {
+    pub fn new(n_arms: usize, scalarization: Scalarization) -> ScalarizedUcb {
+        ScalarizedUcb { scalarization, stats: VectorStats::new(n_arms) }
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        let total = self.stats.total().max(1) as f64;
+        (0..self.stats.n_arms()).map(|i| match self.stats.counts[i] {
+            0 => f64::INFINITY,
+            n => self.scalarization.apply(&self.stats.mean(i)) + (2. * total.ln() / n as f64).sqrt(),
+        }).collect()
+    }
+}
+
+impl Agent for ScalarizedUcb {
+    fn name(&self) -> String {
+        match &self.scalarization {
+            Scalarization::Linear(weights) => format!("linear-ucb({:?})", weights),
+            Scalarization::Chebyshev(weights) => format!("chebyshev-ucb({:?})", weights),
+        }
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        one_hot(self.stats.n_arms(), argmax(&self.indices()))
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        argmax(&self.indices())
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.stats.update(arm, &[reward])
+    }
+    fn update_vector(&mut self, arm: usize, reward: &[f64]) {
+        self.stats.update(arm, reward)
+    }
+    fn reset(&mut self) {
+        self.stats.reset()
+    }
+}
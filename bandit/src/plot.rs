@@ -0,0 +1,88 @@
+use std::fmt::Write;
+use std::vec::Vec;
+use crate::simulation::Curve;
+
+
+const WIDTH: f64 = 720.;
+const HEIGHT: f64 = 440.;
+const MARGIN: f64 = 60.;
+const MAX_POINTS: usize = 400;
+const COLORS: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+];
+
+fn sample_points(len: usize) -> Vec<usize> {
+    if len == 0 {
+        return vec![]
+    }
+    let step = (len / MAX_POINTS).max(1);
+    let mut points: Vec<usize> = (0..len).step_by(step).collect();
+    if points.last() != Some(&(len - 1)) {
+        points.push(len - 1);
+    }
+    points
+}
+
+fn path(points: &[(f64, f64)]) -> String {
+    points.iter().enumerate()
+        .map(|(i, (x, y))| format!("{}{:.1},{:.1}", if i == 0 { "M" } else { "L" }, x, y))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+pub fn regret_svg(title: &str, curves: &[&Curve]) -> String {
+    let horizon = curves.iter().map(|c| c.config.horizon).max().unwrap_or(0);
+    let bound = curves.iter().find(|c| c.lower_bound.is_some());
+    let y_max = curves.iter()
+        .flat_map(|c| c.upper.iter())
+        .chain(bound.iter().flat_map(|c| c.lower_bound.as_ref().unwrap().iter()))
+        .cloned()
+        .fold(0., f64::max)
+        .max(1e-9);
+    let plot_w = WIDTH - 2. * MARGIN;
+    let plot_h = HEIGHT - 2. * MARGIN;
+    let x = |round: usize| MARGIN + plot_w * round as f64 / horizon.max(1) as f64;
+    let y = |v: f64| HEIGHT - MARGIN - plot_h * v.max(0.) / y_max;
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="12">"#, WIDTH, HEIGHT).unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle" font-size="15">{}</text>"#, WIDTH / 2., MARGIN / 2., escape(title)).unwrap();
+    writeln!(svg, r#"<line x1="{m}" y1="{b}" x2="{r}" y2="{b}" stroke="black"/><line x1="{m}" y1="{m}" x2="{m}" y2="{b}" stroke="black"/>"#,
+        m = MARGIN, b = HEIGHT - MARGIN, r = WIDTH - MARGIN).unwrap();
+    for i in 0..=4 {
+        let v = y_max * i as f64 / 4.;
+        writeln!(svg, r#"<text x="{}" y="{:.1}" text-anchor="end">{:.1}</text>"#, MARGIN - 6., y(v) + 4., v).unwrap();
+        let t = horizon * i / 4;
+        writeln!(svg, r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#, MARGIN + plot_w * i as f64 / 4., HEIGHT - MARGIN + 18., t).unwrap();
+    }
+    writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle">round</text>"#, WIDTH / 2., HEIGHT - 12.).unwrap();
+    writeln!(svg, r#"<text x="14" y="{}" text-anchor="middle" transform="rotate(-90 14 {})">mean regret</text>"#, HEIGHT / 2., HEIGHT / 2.).unwrap();
+
+    for (i, curve) in curves.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+        let points = sample_points(curve.mean.len());
+        let mut band: Vec<(f64, f64)> = points.iter().map(|i| (x(curve.rounds[*i]), y(curve.upper[*i]))).collect();
+        band.extend(points.iter().rev().map(|i| (x(curve.rounds[*i]), y(curve.lower[*i]))));
+        let line: Vec<(f64, f64)> = points.iter().map(|i| (x(curve.rounds[*i]), y(curve.mean[*i]))).collect();
+        writeln!(svg, r#"<path d="{} Z" fill="{}" fill-opacity="0.2" stroke="none"/>"#, path(&band), color).unwrap();
+        writeln!(svg, r#"<path d="{}" fill="none" stroke="{}" stroke-width="2"/>"#, path(&line), color).unwrap();
+        let ly = MARGIN + 16. * i as f64;
+        writeln!(svg, r#"<rect x="{}" y="{}" width="12" height="12" fill="{}"/><text x="{}" y="{}">{}</text>"#,
+            MARGIN + 10., ly, color, MARGIN + 28., ly + 10., escape(&curve.agent)).unwrap();
+    }
+    if let Some(curve) = bound {
+        let values = curve.lower_bound.as_ref().unwrap();
+        let line: Vec<(f64, f64)> = sample_points(values.len()).iter().map(|i| (x(curve.rounds[*i]), y(values[*i]))).collect();
+        writeln!(svg, r#"<path d="{}" fill="none" stroke="black" stroke-width="1.5" stroke-dasharray="6 4"/>"#, path(&line)).unwrap();
+        let ly = MARGIN + 16. * curves.len() as f64;
+        writeln!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-dasharray="4 2"/><text x="{}" y="{}">asymptotic (Lai-Robbins) rate</text>"#,
+            MARGIN + 10., ly + 6., MARGIN + 22., ly + 6., MARGIN + 28., ly + 10.).unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, HashMap};
+use std::vec::Vec;
+use crate::game::{BinarySlot, GaussianSlot, ParetoSlot, Replayable, StudentTSlot, VectorSlot};
+use crate::markov::MarkovSlot;
+use crate::rng::SlotRng;
+use crate::spec::ArmSpec;
+
+
+pub type SlotParams = BTreeMap<String, f64>;
+
+type Constructor = Box<dyn Fn(&SlotParams, Box<dyn SlotRng>) -> Result<Box<dyn Replayable + Send>, String> + Send + Sync>;
+type Composite = Box<dyn Fn(&ArmSpec, &SlotRegistry, &mut dyn FnMut() -> Box<dyn SlotRng>) -> Result<Box<dyn Replayable + Send>, String> + Send + Sync>;
+
+pub fn param(params: &SlotParams, key: &str) -> Result<f64, String> {
+    params.get(key).cloned().ok_or(format!("missing `{}`", key))
+}
+
+pub fn param_or(params: &SlotParams, key: &str, default: f64) -> f64 {
+    params.get(key).cloned().unwrap_or(default)
+}
+
+pub fn parse_params(text: &str) -> Result<SlotParams, String> {
+    text.split(',').filter(|x| !x.trim().is_empty()).map(|pair| {
+        let mut kv = pair.splitn(2, '=');
+        let key = kv.next().unwrap_or("").trim();
+        let value = kv.next().ok_or(format!("expected key=value, got `{}`", pair))?;
+        let value = value.trim().parse::<f64>().map_err(|_| format!("`{}`: invalid number `{}`", key, value))?;
+        Ok((key.to_string(), value))
+    }).collect()
+}
+
+pub fn expect_keys(params: &SlotParams, keys: &[&str]) -> Result<(), String> {
+    match params.keys().find(|k| !keys.contains(&k.as_str())) {
+        Some(k) => Err(format!("unknown parameter `{}` (expected one of: {})", k, keys.join(", "))),
+        None => Ok(())
+    }
+}
+
+
+pub struct SlotRegistry {
+    kinds: HashMap<String, Constructor>,
+    composites: HashMap<String, Composite>,
+}
+
+impl SlotRegistry {
+    pub fn new() -> SlotRegistry {
+        SlotRegistry { kinds: HashMap::new(), composites: HashMap::new() }
+    }
+    pub fn register<F>(&mut self, kind: &str, constructor: F)
+        where
+            F: Fn(&SlotParams, Box<dyn SlotRng>) -> Result<Box<dyn Replayable + Send>, String> + Send + Sync + 'static
+    {
+        self.kinds.insert(kind.to_string(), Box::new(constructor));
+    }
+    pub fn register_composite<F>(&mut self, kind: &str, constructor: F)
+        where
+            F: Fn(&ArmSpec, &SlotRegistry, &mut dyn FnMut() -> Box<dyn SlotRng>) -> Result<Box<dyn Replayable + Send>, String> + Send + Sync + 'static
+    {
+        self.composites.insert(kind.to_string(), Box::new(constructor));
+    }
+    pub fn create(&self, kind: &str, params: &SlotParams, rng: Box<dyn SlotRng>) -> Result<Box<dyn Replayable + Send>, String> {
+        match self.kinds.get(kind) {
+            Some(constructor) => constructor(params, rng),
+            None if self.composites.contains_key(kind) => Err(format!("`{}` arms are built from a full arm spec, not from parameters", kind)),
+            None => Err(format!("unknown arm type `{}` (registered: {})", kind, self.kinds().join(", ")))
+        }
+    }
+    pub fn create_composite(&self, spec: &ArmSpec, next: &mut dyn FnMut() -> Box<dyn SlotRng>) -> Option<Result<Box<dyn Replayable + Send>, String>> {
+        self.composites.get(&spec.kind).map(|constructor| constructor(spec, self, next))
+    }
+    pub fn contains(&self, kind: &str) -> bool {
+        self.kinds.contains_key(kind) || self.composites.contains_key(kind)
+    }
+    pub fn kinds(&self) -> Vec<String> {
+        let mut kinds: Vec<String> = self.kinds.keys().chain(self.composites.keys()).cloned().collect();
+        kinds.sort();
+        kinds
+    }
+}
+
+impl Default for SlotRegistry {
+    fn default() -> SlotRegistry {
+        let mut registry = SlotRegistry::new();
+        for kind in ["bernoulli", "binary"].iter() {
+            registry.register(kind, |params, rng| Ok(Box::new(BinarySlot::from_params(params, rng)?)));
+        }
+        for kind in ["gaussian", "normal"].iter() {
+            registry.register(kind, |params, rng| Ok(Box::new(GaussianSlot::from_params(params, rng)?)));
+        }
+        registry.register("pareto", |params, rng| Ok(Box::new(ParetoSlot::from_params(params, rng)?)));
+        for kind in ["student-t", "studentt"].iter() {
+            registry.register(kind, |params, rng| Ok(Box::new(StudentTSlot::from_params(params, rng)?)));
+        }
+        registry.register_composite("vector", |spec, registry, next| {
+            let components = spec.components.iter().enumerate().map(|(i, component)| {
+                registry.create(&component.kind, &component.params, next())
+                    .map_err(|e| format!("{}: {}", component.label(i), e))
+            }).collect::<Result<Vec<_>, String>>()?;
+            Ok(Box::new(VectorSlot::new(components)?))
+        });
+        registry.register_composite("markov", |spec, _, next| match &spec.markov {
+            Some(arm) => Ok(Box::new(MarkovSlot::new(arm.clone(), next())?)),
+            None => Err("a markov arm needs a `markov` table".to_string())
+        });
+        registry
+    }
+}
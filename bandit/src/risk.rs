@@ -0,0 +1,292 @@
+use std::str::FromStr;
+use std::vec::Vec;
+use rand::RngCore;
+use crate::agent::{argmax, one_hot, Agent};
+
+
+pub fn mean(samples: &[f64]) -> f64 {
+    if samples.is_empty() { 0. } else { samples.iter().sum::<f64>() / samples.len() as f64 }
+}
+
+pub fn variance(samples: &[f64]) -> f64 {
+    let m = mean(samples);
+    if samples.is_empty() { 0. } else { samples.iter().map(|x| (x - m).powi(2)).sum::<f64>() / samples.len() as f64 }
+}
+
+fn sorted(samples: &[f64]) -> Vec<f64> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted
+}
+
+pub fn lower_tail(sorted: &[(f64, f64)], alpha: f64) -> f64 {
+    let mut mass = 0.;
+    let mut total = 0.;
+    for (value, weight) in sorted {
+        let take = weight.min(alpha - mass);
+        if take <= 0. {
+            break
+        }
+        total += take * value;
+        mass += take;
+    }
+    if mass > 0. { total / mass } else { 0. }
+}
+
+pub fn cvar_sorted(sorted: &[f64], alpha: f64) -> f64 {
+    let weight = 1. / sorted.len().max(1) as f64;
+    let atoms: Vec<(f64, f64)> = sorted.iter().map(|x| (*x, weight)).collect();
+    lower_tail(&atoms, alpha)
+}
+
+pub fn cvar(samples: &[f64], alpha: f64) -> f64 {
+    cvar_sorted(&sorted(samples), alpha)
+}
+
+pub fn loss_probability(samples: &[f64], threshold: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.
+    }
+    samples.iter().filter(|x| **x < threshold).count() as f64 / samples.len() as f64
+}
+
+pub fn discounted(rewards: &[f64], gamma: f64) -> f64 {
+    let mut weight = 1.;
+    rewards.iter().map(|r| {
+        let term = weight * r;
+        weight *= gamma;
+        term
+    }).sum()
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    Sum,
+    Mean,
+    MeanVariance(f64),
+    Cvar(f64),
+    LossProbability(f64),
+    Discounted(f64),
+}
+
+impl Objective {
+    pub fn evaluate(&self, rewards: &[f64]) -> f64 {
+        match self {
+            Objective::Sum => rewards.iter().sum(),
+            Objective::Mean => mean(rewards),
+            Objective::MeanVariance(rho) => rho * mean(rewards) - variance(rewards),
+            Objective::Cvar(alpha) => cvar(rewards, *alpha),
+            Objective::LossProbability(threshold) => loss_probability(rewards, *threshold),
+            Objective::Discounted(gamma) => discounted(rewards, *gamma),
+        }
+    }
+}
+
+impl FromStr for Objective {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Objective, String> {
+        let mut parts = s.splitn(2, ':');
+        let kind = parts.next().unwrap_or("");
+        let param = parts.next().map(|x| x.parse::<f64>().map_err(|e| format!("{}: {}", s, e))).transpose()?;
+        match kind {
+            "sum" => Ok(Objective::Sum),
+            "mean" => Ok(Objective::Mean),
+            "mv" | "mean-variance" => Ok(Objective::MeanVariance(param.unwrap_or(1.))),
+            "cvar" => match param.unwrap_or(0.05) {
+                alpha if alpha > 0. && alpha <= 1. => Ok(Objective::Cvar(alpha)),
+                alpha => Err(format!("CVaR level must be in (0, 1], got {}", alpha))
+            },
+            "ploss" | "loss" => Ok(Objective::LossProbability(param.unwrap_or(0.))),
+            "discounted" => match param.unwrap_or(0.99) {
+                gamma if gamma > 0. && gamma <= 1. => Ok(Objective::Discounted(gamma)),
+                gamma => Err(format!("discount must be in (0, 1], got {}", gamma))
+            },
+            _ => Err(format!("Unknown objective: {}", s))
+        }
+    }
+}
+
+impl std::fmt::Display for Objective {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Objective::Sum => write!(f, "sum"),
+            Objective::Mean => write!(f, "mean"),
+            Objective::MeanVariance(rho) => write!(f, "mv:{}", rho),
+            Objective::Cvar(alpha) => write!(f, "cvar:{}", alpha),
+            Objective::LossProbability(threshold) => write!(f, "ploss:{}", threshold),
+            Objective::Discounted(gamma) => write!(f, "discounted:{}", gamma),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct SortedRewards {
+    pub samples: Vec<Vec<f64>>,
+}
+
+impl SortedRewards {
+    pub fn new(n_arms: usize) -> SortedRewards {
+        SortedRewards { samples: vec![vec![]; n_arms] }
+    }
+    pub fn n_arms(&self) -> usize {
+        self.samples.len()
+    }
+    pub fn count(&self, arm: usize) -> usize {
+        self.samples[arm].len()
+    }
+    pub fn total(&self) -> usize {
+        self.samples.iter().map(|s| s.len()).sum()
+    }
+    pub fn update(&mut self, arm: usize, reward: f64) {
+        let samples = &mut self.samples[arm];
+        let at = samples.partition_point(|x| *x <= reward);
+        samples.insert(at, reward);
+    }
+    pub fn reset(&mut self) {
+        *self = SortedRewards::new(self.n_arms());
+    }
+}
+
+
+pub struct Marab {
+    alpha: f64,
+    c: f64,
+    rewards: SortedRewards,
+}
+
+impl Marab {
+    pub fn new(n_arms: usize, alpha: f64, c: f64) -> Marab {
+        Marab { alpha, c, rewards: SortedRewards::new(n_arms) }
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        let total = (self.rewards.total() as f64 * self.alpha).ceil().max(1.);
+        (0..self.rewards.n_arms()).map(|i| match self.rewards.count(i) {
+            0 => f64::INFINITY,
+            n => {
+                let tail = (n as f64 * self.alpha).ceil();
+                cvar_sorted(&self.rewards.samples[i], self.alpha) - self.c * (total.ln() / tail).sqrt()
+            }
+        }).collect()
+    }
+}
+
+impl Agent for Marab {
+    fn name(&self) -> String {
+        format!("marab({}, {})", self.alpha, self.c)
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        one_hot(self.rewards.n_arms(), argmax(&self.indices()))
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        argmax(&self.indices())
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.rewards.update(arm, reward)
+    }
+    fn reset(&mut self) {
+        self.rewards.reset()
+    }
+}
+
+
+pub struct CvarUcb {
+    alpha: f64,
+    upper: f64,
+    rewards: SortedRewards,
+}
+
+impl CvarUcb {
+    pub fn new(n_arms: usize, alpha: f64, upper: f64) -> CvarUcb {
+        CvarUcb { alpha, upper, rewards: SortedRewards::new(n_arms) }
+    }
+    pub fn optimistic_cvar(&self, arm: usize) -> f64 {
+        let samples = &self.rewards.samples[arm];
+        let n = samples.len() as f64;
+        let t = self.rewards.total().max(2) as f64;
+        let shift = ((2. * t.ln()) / (2. * n)).sqrt().min(1.);
+        let mut removed = 0.;
+        let mut atoms = vec![];
+        for x in samples {
+            let weight = 1. / n;
+            let keep = (weight - (shift - removed).max(0.)).max(0.);
+            removed += weight - keep;
+            if keep > 0. {
+                atoms.push((x.min(self.upper), keep));
+            }
+        }
+        atoms.push((self.upper, shift));
+        lower_tail(&atoms, self.alpha)
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        (0..self.rewards.n_arms()).map(|i| match self.rewards.count(i) {
+            0 => f64::INFINITY,
+            _ => self.optimistic_cvar(i),
+        }).collect()
+    }
+}
+
+impl Agent for CvarUcb {
+    fn name(&self) -> String {
+        format!("cvar-ucb({})", self.alpha)
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        one_hot(self.rewards.n_arms(), argmax(&self.indices()))
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        argmax(&self.indices())
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.rewards.update(arm, reward)
+    }
+    fn reset(&mut self) {
+        self.rewards.reset()
+    }
+}
+
+
+pub struct MeanVarianceUcb {
+    rho: f64,
+    counts: Vec<usize>,
+    sums: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl MeanVarianceUcb {
+    pub fn new(n_arms: usize, rho: f64) -> MeanVarianceUcb {
+        MeanVarianceUcb { rho, counts: vec![0; n_arms], sums: vec![0.; n_arms], squares: vec![0.; n_arms] }
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        let total = self.counts.iter().sum::<usize>().max(2) as f64;
+        (0..self.counts.len()).map(|i| match self.counts[i] {
+            0 => f64::INFINITY,
+            n => {
+                let n = n as f64;
+                let mean = self.sums[i] / n;
+                let variance = (self.squares[i] / n - mean * mean).max(0.);
+                self.rho * mean - variance + (5. + self.rho) * (total.ln() / (2. * n)).sqrt()
+            }
+        }).collect()
+    }
+}
+
+impl Agent for MeanVarianceUcb {
+    fn name(&self) -> String {
+        format!("mv-ucb({})", self.rho)
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        one_hot(self.counts.len(), argmax(&self.indices()))
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        argmax(&self.indices())
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.counts[arm] += 1;
+        self.sums[arm] += reward;
+        self.squares[arm] += reward * reward;
+    }
+    fn reset(&mut self) {
+        *self = MeanVarianceUcb::new(self.counts.len(), self.rho);
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+use std::vec::Vec;
+use rand::{Error, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+
+pub trait SlotRng: RngCore + Send {
+    fn box_clone(&self) -> Box<dyn SlotRng>;
+}
+
+impl<T: RngCore + Clone + Send + 'static> SlotRng for T {
+    fn box_clone(&self) -> Box<dyn SlotRng> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn SlotRng> {
+    fn clone(&self) -> Box<dyn SlotRng> {
+        (**self).box_clone()
+    }
+}
+
+
+pub trait RandomSource: Send + Sync {
+    fn name(&self) -> String;
+    fn generator(&self, seed: u64) -> Box<dyn SlotRng>;
+}
+
+pub struct StdSource;
+
+impl RandomSource for StdSource {
+    fn name(&self) -> String {
+        "std".to_string()
+    }
+    fn generator(&self, seed: u64) -> Box<dyn SlotRng> {
+        Box::new(StdRng::seed_from_u64(seed))
+    }
+}
+
+pub struct XoshiroSource;
+
+impl RandomSource for XoshiroSource {
+    fn name(&self) -> String {
+        "xoshiro".to_string()
+    }
+    fn generator(&self, seed: u64) -> Box<dyn SlotRng> {
+        Box::new(Xoshiro256PlusPlus::seed_from_u64(seed))
+    }
+}
+
+pub struct ScriptedSource {
+    values: Arc<Vec<f64>>,
+}
+
+impl ScriptedSource {
+    pub fn new(values: Vec<f64>) -> ScriptedSource {
+        ScriptedSource { values: Arc::new(values) }
+    }
+}
+
+impl RandomSource for ScriptedSource {
+    fn name(&self) -> String {
+        "scripted".to_string()
+    }
+    fn generator(&self, _seed: u64) -> Box<dyn SlotRng> {
+        Box::new(ScriptedRng { values: Arc::clone(&self.values), pos: 0 })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptedRng {
+    values: Arc<Vec<f64>>,
+    pos: usize,
+}
+
+impl RngCore for ScriptedRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+    fn next_u64(&mut self) -> u64 {
+        if self.values.is_empty() {
+            return 0
+        }
+        let value = self.values[self.pos % self.values.len()].clamp(0., 1. - f64::EPSILON);
+        self.pos += 1;
+        ((value * (1u64 << 53) as f64) as u64) << 11
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+pub fn source_by_name(name: &str) -> Result<Arc<dyn RandomSource>, String> {
+    match name {
+        "std" | "chacha" => Ok(Arc::new(StdSource)),
+        "xoshiro" => Ok(Arc::new(XoshiroSource)),
+        _ => Err(format!("Unknown random source: {}", name))
+    }
+}
+
+
+#[derive(Clone)]
+pub struct ReplayRng {
+    initial: Box<dyn SlotRng>,
+    current: Box<dyn SlotRng>,
+}
+
+impl ReplayRng {
+    pub fn new(rng: Box<dyn SlotRng>) -> ReplayRng {
+        ReplayRng { initial: rng.clone(), current: rng }
+    }
+    pub fn rewind(&mut self) {
+        self.current = self.initial.clone();
+    }
+}
+
+impl RngCore for ReplayRng {
+    fn next_u32(&mut self) -> u32 {
+        self.current.next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.current.next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.current.fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.current.try_fill_bytes(dest)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::Rng;
+    use crate::game::{EpisodeMode, Game, RewardType};
+    use super::*;
+
+    fn scripted_game() -> Game {
+        // The master draws (seed, p) per arm, so arm 0 gets p = 0.5 and arm 1
+        // gets p = 0.25; every slot then replays the script from the start.
+        Game::with_source(2, RewardType::Binary, 0, Arc::new(ScriptedSource::new(vec![0.125, 0.5, 0.875, 0.25])))
+    }
+
+    #[test]
+    fn scripted_rng_repeats_its_values() {
+        let mut rng = ScriptedSource::new(vec![0.25, 0.75]).generator(42);
+        let values: Vec<f64> = (0..4).map(|_| rng.gen()).collect();
+        assert_eq!(values, vec![0.25, 0.75, 0.25, 0.75]);
+    }
+
+    #[test]
+    fn scripted_game_pays_the_script() {
+        let mut game = scripted_game();
+        assert_eq!(game.profiles(), vec![0.5, 0.25]);
+        game.start(None);
+        let rewards: Vec<f64> = (0..5).map(|_| game.play(0).unwrap()).collect();
+        assert_eq!(rewards, vec![1., 0., 0., 1., 1.]);
+        let rewards: Vec<f64> = (0..4).map(|_| game.play(1).unwrap()).collect();
+        assert_eq!(rewards, vec![1., 0., 0., 0.]);
+    }
+
+    #[test]
+    fn replay_rewinds_the_script() {
+        let mut game = scripted_game();
+        game.set_mode(EpisodeMode::Replay);
+        game.start(None);
+        let first: Vec<f64> = [0, 1, 0, 1].iter().map(|i| game.play(*i).unwrap()).collect();
+        game.start(None);
+        let second: Vec<f64> = [0, 1, 0, 1].iter().map(|i| game.play(*i).unwrap()).collect();
+        assert_eq!(first, vec![1., 1., 0., 0.]);
+        assert_eq!(first, second);
+    }
+}
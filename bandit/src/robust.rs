@@ -0,0 +1,180 @@
+use std::vec::Vec;
+use rand::RngCore;
+use crate::agent::{argmax, one_hot, Agent};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Estimator {
+    MedianOfMeans,
+    TruncatedMean,
+    Catoni,
+}
+
+pub fn psi(x: f64) -> f64 {
+    x.signum() * (1. + x.abs() + x * x / 2.).ln()
+}
+
+fn psi_prime(x: f64) -> f64 {
+    (1. + x.abs()) / (1. + x.abs() + x * x / 2.)
+}
+
+pub fn median_of_means(prefix: &[f64], blocks: usize) -> f64 {
+    let n = prefix.len() - 1;
+    let blocks = blocks.max(1).min(n.max(1));
+    let size = n / blocks;
+    if size == 0 {
+        return 0.
+    }
+    let mut means: Vec<f64> = (0..blocks)
+        .map(|b| (prefix[(b + 1) * size] - prefix[b * size]) / size as f64)
+        .collect();
+    means.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    if blocks % 2 == 1 { means[blocks / 2] } else { (means[blocks / 2 - 1] + means[blocks / 2]) / 2. }
+}
+
+pub fn catoni(samples: &[f64], alpha: f64, guess: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.
+    }
+    let mut lo = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+    let mut hi = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mut mu = guess.max(lo).min(hi);
+    for _ in 0..100 {
+        let f: f64 = samples.iter().map(|x| psi(alpha * (x - mu))).sum();
+        if f.abs() < 1e-10 * samples.len() as f64 || hi - lo < 1e-12 {
+            break
+        }
+        if f > 0. { lo = mu } else { hi = mu }
+        let slope: f64 = alpha * samples.iter().map(|x| psi_prime(alpha * (x - mu))).sum::<f64>();
+        let next = mu + f / slope;
+        mu = if next > lo && next < hi { next } else { (lo + hi) / 2. };
+    }
+    mu
+}
+
+
+pub struct RobustUcb {
+    estimator: Estimator,
+    moment: f64,
+    epsilon: f64,
+    rewards: Vec<Vec<f64>>,
+    prefix: Vec<Vec<f64>>,
+    truncated: Vec<f64>,
+    estimates: Vec<f64>,
+    total: usize,
+    epoch: usize,
+}
+
+impl RobustUcb {
+    pub fn new(n_arms: usize, estimator: Estimator, moment: f64, epsilon: f64) -> RobustUcb {
+        RobustUcb {
+            estimator,
+            moment,
+            epsilon: if estimator == Estimator::Catoni { 1. } else { epsilon.clamp(1e-3, 1.) },
+            rewards: vec![vec![]; n_arms],
+            prefix: vec![vec![0.]; n_arms],
+            truncated: vec![0.; n_arms],
+            estimates: vec![0.; n_arms],
+            total: 0,
+            epoch: 1,
+        }
+    }
+    pub fn median_of_means(n_arms: usize, moment: f64, epsilon: f64) -> RobustUcb {
+        RobustUcb::new(n_arms, Estimator::MedianOfMeans, moment, epsilon)
+    }
+    pub fn truncated_mean(n_arms: usize, moment: f64, epsilon: f64) -> RobustUcb {
+        RobustUcb::new(n_arms, Estimator::TruncatedMean, moment, epsilon)
+    }
+    pub fn catoni(n_arms: usize, variance: f64) -> RobustUcb {
+        RobustUcb::new(n_arms, Estimator::Catoni, variance, 1.)
+    }
+    fn confidence(&self) -> f64 {
+        2. * (self.epoch.max(2) as f64).ln()
+    }
+    fn threshold(&self, s: usize) -> f64 {
+        (self.moment * s as f64 / self.confidence()).powf(1. / (1. + self.epsilon))
+    }
+    fn retruncate(&mut self, arm: usize) {
+        let total = self.rewards[arm].iter().enumerate()
+            .filter(|(s, x)| x.abs() <= self.threshold(s + 1))
+            .map(|(_, x)| x)
+            .sum();
+        self.truncated[arm] = total;
+    }
+    fn estimate(&self, arm: usize) -> f64 {
+        let n = self.rewards[arm].len();
+        let log = self.confidence();
+        match self.estimator {
+            Estimator::MedianOfMeans => {
+                let blocks = ((8. * log + 1.).floor() as usize).min(n / 2);
+                median_of_means(&self.prefix[arm], blocks)
+            }
+            Estimator::TruncatedMean => self.truncated[arm] / n as f64,
+            Estimator::Catoni => {
+                let alpha = (2. * log / (n as f64 * self.moment)).sqrt();
+                catoni(&self.rewards[arm], alpha, self.estimates[arm])
+            }
+        }
+    }
+    fn bonus(&self, n: usize) -> f64 {
+        let log = self.confidence();
+        let power = self.epsilon / (1. + self.epsilon);
+        match self.estimator {
+            Estimator::MedianOfMeans => (12. * self.moment).powf(1. / (1. + self.epsilon)) * (16. * (log + 0.125) / n as f64).powf(power),
+            Estimator::TruncatedMean => 4. * self.moment.powf(1. / (1. + self.epsilon)) * (log / n as f64).powf(power),
+            Estimator::Catoni => 2. * (self.moment * log / n as f64).sqrt(),
+        }
+    }
+    pub fn estimates(&self) -> Vec<f64> {
+        self.estimates.clone()
+    }
+    pub fn indices(&self) -> Vec<f64> {
+        (0..self.rewards.len()).map(|i| match self.rewards[i].len() {
+            0 => f64::INFINITY,
+            n => self.estimates[i] + self.bonus(n),
+        }).collect()
+    }
+}
+
+impl Agent for RobustUcb {
+    fn name(&self) -> String {
+        match self.estimator {
+            Estimator::MedianOfMeans => format!("mom-ucb({}, {})", self.moment, self.epsilon),
+            Estimator::TruncatedMean => format!("trunc-ucb({}, {})", self.moment, self.epsilon),
+            Estimator::Catoni => format!("catoni-ucb({})", self.moment),
+        }
+    }
+    fn probs(&mut self, _rng: &mut dyn RngCore) -> Vec<f64> {
+        one_hot(self.rewards.len(), argmax(&self.indices()))
+    }
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        argmax(&self.indices())
+    }
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.total += 1;
+        self.rewards[arm].push(reward);
+        let last = *self.prefix[arm].last().unwrap();
+        self.prefix[arm].push(last + reward);
+        let epoch = self.total.next_power_of_two();
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            for i in 0..self.rewards.len() {
+                if !self.rewards[i].is_empty() {
+                    self.retruncate(i);
+                    self.estimates[i] = self.estimate(i);
+                }
+            }
+        } else {
+            let n = self.rewards[arm].len();
+            if reward.abs() <= self.threshold(n) {
+                self.truncated[arm] += reward;
+            }
+            if self.estimator != Estimator::Catoni || n.is_power_of_two() {
+                self.estimates[arm] = self.estimate(arm);
+            }
+        }
+    }
+    fn reset(&mut self) {
+        *self = RobustUcb::new(self.rewards.len(), self.estimator, self.moment, self.epsilon);
+    }
+}
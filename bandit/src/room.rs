@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+use serde::{Serialize, Deserialize};
+use crate::game::Game;
+
+
+pub type Rooms = Mutex<HashMap<String, Room>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collision {
+    Split,
+    Zero,
+}
+
+impl FromStr for Collision {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Collision, String> {
+        match s {
+            "split" => Ok(Collision::Split),
+            "zero" => Ok(Collision::Zero),
+            _ => Err(format!("Unknown collision model: {}", s))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Player {
+    pub score: f64,
+    pub pulls: usize,
+    pub collisions: usize,
+    pub forfeits: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundResult {
+    pub round: usize,
+    pub choices: BTreeMap<String, usize>,
+    pub payouts: BTreeMap<String, f64>,
+    pub forfeited: Vec<String>,
+    pub remaining: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Standing {
+    pub player: String,
+    pub score: f64,
+    pub pulls: usize,
+    pub collisions: usize,
+    pub forfeits: usize,
+}
+
+pub struct Room {
+    game: Game,
+    collision: Collision,
+    remaining: Vec<Option<f64>>,
+    rounds: Option<usize>,
+    timeout: Option<Duration>,
+    round_started: Option<Instant>,
+    players: BTreeMap<String, Player>,
+    pending: BTreeMap<String, usize>,
+    results: Vec<RoundResult>,
+}
+
+impl Room {
+    pub fn new(game: Game, collision: Collision, capacity: Option<f64>, rounds: Option<usize>, timeout: Option<Duration>) -> Room {
+        let remaining = vec![capacity; game.n_machines()];
+        let mut game = game;
+        game.start(None);
+        Room {
+            game, collision, remaining, rounds, timeout,
+            round_started: None,
+            players: BTreeMap::new(),
+            pending: BTreeMap::new(),
+            results: vec![],
+        }
+    }
+    pub fn n_machines(&self) -> usize {
+        self.game.n_machines()
+    }
+    pub fn round(&self) -> usize {
+        self.results.len()
+    }
+    pub fn is_finished(&self) -> bool {
+        self.rounds.map(|r| self.round() >= r).unwrap_or(false)
+    }
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+    pub fn n_players(&self) -> usize {
+        self.players.len()
+    }
+    pub fn finish(&mut self) {
+        self.pending.clear();
+        self.round_started = None;
+        self.rounds = Some(self.round());
+    }
+    pub fn deadline(&self) -> Option<Instant> {
+        self.timeout.and_then(|timeout| self.round_started.map(|started| started + timeout))
+    }
+    pub fn expire(&mut self, now: Instant) -> Result<Option<RoundResult>, String> {
+        match self.deadline() {
+            Some(deadline) if now >= deadline && !self.is_finished() => Ok(Some(self.resolve()?)),
+            _ => Ok(None)
+        }
+    }
+    pub fn join(&mut self, name: &str) -> Result<(), String> {
+        if self.is_finished() {
+            return Err("The room is finished.".to_string())
+        }
+        if self.players.contains_key(name) {
+            return Err(format!("{} has already joined.", name))
+        }
+        self.players.insert(name.to_string(), Player::default());
+        Ok(())
+    }
+    pub fn waiting_for(&self) -> Vec<String> {
+        self.players.keys().filter(|p| !self.pending.contains_key(*p)).cloned().collect()
+    }
+    pub fn submit(&mut self, name: &str, index: usize, now: Instant) -> Result<Option<RoundResult>, String> {
+        self.expire(now)?;
+        if self.is_finished() {
+            return Err("The room is finished.".to_string())
+        }
+        if !self.players.contains_key(name) {
+            return Err(format!("{} has not joined this room.", name))
+        }
+        if index >= self.n_machines() {
+            return Err("Index out of range.".to_string())
+        }
+        if self.pending.contains_key(name) {
+            return Err(format!("{} has already chosen this round.", name))
+        }
+        if self.pending.is_empty() {
+            self.round_started = Some(now);
+        }
+        self.pending.insert(name.to_string(), index);
+        if self.waiting_for().is_empty() {
+            Ok(Some(self.resolve()?))
+        } else {
+            Ok(None)
+        }
+    }
+    fn resolve(&mut self) -> Result<RoundResult, String> {
+        let mut claimants: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for (player, index) in self.pending.iter() {
+            claimants.entry(*index).or_default().push(player.clone());
+        }
+        if !self.game.is_playing() {
+            return Err("The room's game has ended.".to_string())
+        }
+        if let Some(horizon) = self.game.limit() {
+            if self.game.history().len() + claimants.len() > horizon {
+                return Err("The room's game has too few rounds left.".to_string())
+            }
+        }
+        let mut remaining = self.remaining.clone();
+        let mut shares = vec![];
+        for (index, players) in claimants.iter() {
+            let mut payout = self.game.play(*index)?;
+            if let Some(left) = remaining[*index] {
+                payout = payout.min(left).max(0.);
+            }
+            let share = match (self.collision, players.len()) {
+                (_, 1) => payout,
+                (Collision::Split, n) => payout / n as f64,
+                (Collision::Zero, _) => 0.,
+            };
+            if let Some(left) = remaining[*index].as_mut() {
+                *left -= share * players.len() as f64;
+            }
+            shares.push(share);
+        }
+        let mut payouts = BTreeMap::new();
+        for (players, share) in claimants.values().zip(shares) {
+            for player in players.iter() {
+                let stats = self.players.get_mut(player).unwrap();
+                stats.score += share;
+                stats.pulls += 1;
+                stats.collisions += (players.len() > 1) as usize;
+                payouts.insert(player.clone(), share);
+            }
+        }
+        let forfeited = self.waiting_for();
+        for player in forfeited.iter() {
+            self.players.get_mut(player).unwrap().forfeits += 1;
+        }
+        self.remaining = remaining;
+        self.round_started = None;
+        let choices = std::mem::take(&mut self.pending);
+        let result = RoundResult { round: self.round() + 1, choices, payouts, forfeited, remaining: self.remaining.clone() };
+        self.results.push(result.clone());
+        Ok(result)
+    }
+    pub fn last_result(&self) -> Option<&RoundResult> {
+        self.results.last()
+    }
+    pub fn results_since(&self, round: usize) -> &[RoundResult] {
+        &self.results[round.min(self.results.len())..]
+    }
+    pub fn scoreboard(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self.players.iter()
+            .map(|(name, p)| Standing { player: name.clone(), score: p.score, pulls: p.pulls, collisions: p.collisions, forfeits: p.forfeits })
+            .collect();
+        standings.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.player.cmp(&b.player)));
+        standings
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::spec::GameSpec;
+    use super::*;
+
+    fn sure_game(horizon: Option<usize>) -> Game {
+        let horizon = horizon.map(|h| format!("horizon = {}\n", h)).unwrap_or_default();
+        let spec = format!("{}seed = 1\n[[arms]]\ntype = \"bernoulli\"\np = 1.0\n[[arms]]\ntype = \"bernoulli\"\np = 1.0\n", horizon);
+        Game::from_spec(&GameSpec::from_toml(&spec).unwrap()).unwrap()
+    }
+
+    fn room(collision: Collision, capacity: Option<f64>, timeout: Option<Duration>) -> Room {
+        let mut room = Room::new(sure_game(None), collision, capacity, None, timeout);
+        room.join("a").unwrap();
+        room.join("b").unwrap();
+        room
+    }
+
+    #[test]
+    fn split_shares_a_collision() {
+        let now = Instant::now();
+        let mut room = room(Collision::Split, None, None);
+        assert!(room.submit("a", 0, now).unwrap().is_none());
+        let result = room.submit("b", 0, now).unwrap().unwrap();
+        assert_eq!(result.payouts["a"], 0.5);
+        assert_eq!(result.payouts["b"], 0.5);
+        assert!(room.scoreboard().iter().all(|s| s.collisions == 1));
+    }
+
+    #[test]
+    fn zero_pays_nothing_on_a_collision() {
+        let now = Instant::now();
+        let mut room = room(Collision::Zero, None, None);
+        room.submit("a", 1, now).unwrap();
+        let result = room.submit("b", 1, now).unwrap().unwrap();
+        assert_eq!(result.payouts["a"], 0.);
+        assert_eq!(result.payouts["b"], 0.);
+        let result = {
+            room.submit("a", 0, now).unwrap();
+            room.submit("b", 1, now).unwrap().unwrap()
+        };
+        assert_eq!(result.payouts["a"], 1.);
+        assert_eq!(result.payouts["b"], 1.);
+    }
+
+    #[test]
+    fn capacity_is_depleted() {
+        let now = Instant::now();
+        let mut room = room(Collision::Split, Some(2.5), None);
+        let mut paid = vec![];
+        for _ in 0..3 {
+            room.submit("a", 0, now).unwrap();
+            let result = room.submit("b", 1, now).unwrap().unwrap();
+            paid.push(result.payouts["a"]);
+        }
+        assert_eq!(paid, vec![1., 1., 0.5]);
+        assert_eq!(room.last_result().unwrap().remaining, vec![Some(0.), Some(0.)]);
+    }
+
+    #[test]
+    fn idle_players_forfeit_after_the_deadline() {
+        let start = Instant::now();
+        let timeout = Duration::from_secs(10);
+        let mut room = room(Collision::Split, None, Some(timeout));
+        room.submit("a", 0, start).unwrap();
+        assert!(room.expire(start + Duration::from_secs(5)).unwrap().is_none());
+        let result = room.expire(start + timeout).unwrap().unwrap();
+        assert_eq!(result.payouts.keys().collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(result.forfeited, vec!["b".to_string()]);
+        let late = start + Duration::from_secs(25);
+        room.submit("a", 1, start + Duration::from_secs(12)).unwrap();
+        assert!(room.submit("b", 1, late).is_ok());
+        assert_eq!(room.round(), 2);
+        assert_eq!(room.results_since(1)[0].forfeited, vec!["b".to_string()]);
+        let standings = room.scoreboard();
+        assert_eq!(standings.iter().find(|s| s.player == "b").unwrap().forfeits, 2);
+    }
+
+    #[test]
+    fn a_short_horizon_leaves_the_round_pending() {
+        let now = Instant::now();
+        let mut room = Room::new(sure_game(Some(1)), Collision::Split, None, None, None);
+        room.join("a").unwrap();
+        room.join("b").unwrap();
+        room.submit("a", 0, now).unwrap();
+        assert!(room.submit("b", 1, now).is_err());
+        assert_eq!(room.round(), 0);
+        assert!(room.waiting_for().is_empty());
+        assert!(room.game().history().is_empty());
+        assert!(room.scoreboard().iter().all(|s| s.score == 0. && s.pulls == 0));
+    }
+}
@@ -0,0 +1,291 @@
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+use rand::RngCore;
+use serde::Serialize;
+use crate::agent::Agent;
+use crate::bounds::{self, Family};
+use crate::feedback::{self, FeedbackGraph, GraphKind};
+use crate::game::{Game, RewardType};
+use crate::pareto;
+use crate::risk::Objective;
+use crate::rng::{RandomSource, StdSource};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Config {
+    pub n_arms: usize,
+    pub horizon: usize,
+    pub reward_type: RewardType,
+}
+
+impl Config {
+    pub fn label(&self) -> String {
+        format!("{}-arms{}-T{}", self.reward_type, self.n_arms, self.horizon)
+    }
+}
+
+pub fn rep_seed(seed: u64, rep: usize) -> u64 {
+    let mut z = seed.wrapping_add((rep as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub fn checkpoints(horizon: usize, stride: usize) -> Vec<usize> {
+    let stride = stride.max(1);
+    let mut rounds: Vec<usize> = (1..=horizon).filter(|t| t % stride == 0).collect();
+    if horizon > 0 && rounds.last() != Some(&horizon) {
+        rounds.push(horizon);
+    }
+    rounds
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Run {
+    pub regret: Vec<f64>,
+    pub best_pulls: usize,
+    pub rounds: usize,
+    pub score: f64,
+    pub lower_bound: Option<f64>,
+}
+
+impl Run {
+    pub fn best_fraction(&self) -> f64 {
+        self.best_pulls as f64 / self.rounds.max(1) as f64
+    }
+}
+
+pub fn failure_rate(runs: &[Run], threshold: f64) -> f64 {
+    let failures = runs.iter().filter(|r| r.best_fraction() < threshold).count();
+    failures as f64 / runs.len().max(1) as f64
+}
+
+pub fn run(agent: &mut dyn Agent, game: &mut Game, horizon: usize, stride: usize, rng: &mut dyn RngCore) -> Result<Run, String> {
+    let graph = FeedbackGraph::bandit(game.n_machines());
+    run_graph(agent, game, &graph, horizon, stride, rng)
+}
+
+pub fn run_graph(agent: &mut dyn Agent, game: &mut Game, graph: &FeedbackGraph, horizon: usize, stride: usize, rng: &mut dyn RngCore) -> Result<Run, String> {
+    let gaps = pareto::pareto_gaps(&game.profile_vectors());
+    let stride = stride.max(1);
+    let mut regret = 0.;
+    let mut best_pulls = 0;
+    let mut curve = Vec::with_capacity(horizon / stride + 1);
+    agent.reset();
+    game.start(None);
+    for t in 1..=horizon {
+        let arm = agent.select(rng);
+        let reward = game.play_vector(arm)?;
+        agent.update_vector(arm, &reward);
+        for j in graph.neighbors(arm).iter().filter(|j| **j != arm) {
+            agent.observe(*j, game.observe(*j)?);
+        }
+        regret += gaps[arm];
+        if gaps[arm] <= 1e-12 {
+            best_pulls += 1;
+        }
+        if t % stride == 0 || t == horizon {
+            curve.push(regret);
+        }
+    }
+    Ok(Run { regret: curve, best_pulls, rounds: horizon, score: game.score(), lower_bound: None })
+}
+
+fn run_rep(agent: &mut dyn Agent, config: &Config, runner: &Runner, graph: &FeedbackGraph, seed: u64, rep: usize) -> Result<Run, String> {
+    let seed = rep_seed(seed, rep);
+    let mut game = Game::with_source(config.n_arms, config.reward_type, seed, Arc::clone(&runner.source));
+    let lower_bound = Family::for_reward_type(config.reward_type).ok()
+        .filter(|_| runner.graph == GraphKind::Bandit)
+        .map(|family| bounds::lai_robbins(&bounds::from_profiles(&game.profiles(), family)).constant);
+    let mut rng = runner.source.generator(seed.rotate_left(32));
+    let mut run = run_graph(agent, &mut game, graph, config.horizon, runner.stride, rng.as_mut())?;
+    run.score = game.score_with(&runner.objective);
+    run.lower_bound = lower_bound;
+    Ok(run)
+}
+
+
+pub struct Report {
+    pub runs: Vec<Run>,
+    pub pulls: usize,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    pub fn throughput(&self) -> f64 {
+        self.pulls as f64 / self.elapsed.as_secs_f64().max(1e-9)
+    }
+    pub fn failure_rate(&self, threshold: f64) -> f64 {
+        failure_rate(&self.runs, threshold)
+    }
+}
+
+#[derive(Clone)]
+pub struct Runner {
+    pub threads: usize,
+    pub stride: usize,
+    pub source: Arc<dyn RandomSource>,
+    pub objective: Objective,
+    pub graph: GraphKind,
+}
+
+impl Default for Runner {
+    fn default() -> Runner {
+        Runner::new()
+    }
+}
+
+impl Runner {
+    pub fn new() -> Runner {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        Runner { threads, stride: 1, source: Arc::new(StdSource), objective: Objective::Sum, graph: GraphKind::Bandit }
+    }
+    pub fn repeat(&self, name: &str, config: &Config, repetitions: usize, seed: u64) -> Result<Report, String> {
+        let graph = self.graph.build(config.n_arms, seed);
+        feedback::by_name(name, &graph, config.horizon)?;
+        let started = Instant::now();
+        let next = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::channel();
+        let workers: Vec<_> = (0..self.threads.max(1).min(repetitions.max(1))).map(|_| {
+            let next = Arc::clone(&next);
+            let sender = sender.clone();
+            let name = name.to_string();
+            let config = *config;
+            let runner = self.clone();
+            let graph = graph.clone();
+            thread::spawn(move || {
+                let mut agent = feedback::by_name(&name, &graph, config.horizon).unwrap();
+                loop {
+                    let rep = next.fetch_add(1, Ordering::SeqCst);
+                    if rep >= repetitions {
+                        break
+                    }
+                    let result = run_rep(agent.as_mut(), &config, &runner, &graph, seed, rep);
+                    if sender.send((rep, result)).is_err() {
+                        break
+                    }
+                }
+            })
+        }).collect();
+        drop(sender);
+        let mut runs = vec![Run::default(); repetitions];
+        let mut failure = None;
+        for (rep, result) in receiver.iter() {
+            match result {
+                Ok(run) => runs[rep] = run,
+                Err(e) => {
+                    next.store(repetitions, Ordering::SeqCst);
+                    failure = Some(e);
+                    break
+                }
+            }
+        }
+        drop(receiver);
+        for worker in workers {
+            worker.join().map_err(|_| "A simulation worker panicked".to_string())?;
+        }
+        if let Some(e) = failure {
+            return Err(e)
+        }
+        Ok(Report { runs, pulls: repetitions * config.horizon, elapsed: started.elapsed() })
+    }
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Curve {
+    pub agent: String,
+    pub config: Config,
+    pub repetitions: usize,
+    pub rounds: Vec<usize>,
+    pub mean: Vec<f64>,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+    pub failure_rate: f64,
+    pub score: f64,
+    pub lower_bound: Option<Vec<f64>>,
+}
+
+impl Curve {
+    pub fn from_runs(agent: &str, config: Config, rounds: Vec<usize>, runs: &[Run], failure_threshold: f64) -> Curve {
+        let n = runs.len().max(1) as f64;
+        let len = rounds.len();
+        let mut mean = vec![0.; len];
+        let mut lower = vec![0.; len];
+        let mut upper = vec![0.; len];
+        for t in 0..len {
+            let m = runs.iter().map(|r| r.regret[t]).sum::<f64>() / n;
+            let var = if runs.len() > 1 {
+                runs.iter().map(|r| (r.regret[t] - m).powi(2)).sum::<f64>() / (n - 1.)
+            } else { 0. };
+            let half = 1.96 * (var / n).sqrt();
+            mean[t] = m;
+            lower[t] = m - half;
+            upper[t] = m + half;
+        }
+        let failure_rate = failure_rate(runs, failure_threshold);
+        let score = runs.iter().map(|r| r.score).sum::<f64>() / n;
+        let lower_bound = runs.iter().map(|r| r.lower_bound).sum::<Option<f64>>()
+            .map(|c| rounds.iter().map(|t| c / n * (*t as f64).ln()).collect());
+        Curve { agent: agent.to_string(), config, repetitions: runs.len(), rounds, mean, lower, upper, failure_rate, score, lower_bound }
+    }
+}
+
+
+pub struct Tournament {
+    pub agents: Vec<String>,
+    pub configs: Vec<Config>,
+    pub repetitions: usize,
+    pub seed: u64,
+    pub failure_threshold: f64,
+    pub runner: Runner,
+}
+
+impl Tournament {
+    pub fn grid(agents: Vec<String>, arms: &[usize], horizons: &[usize], reward_types: &[RewardType]) -> Tournament {
+        let mut configs = vec![];
+        for reward_type in reward_types {
+            for n_arms in arms {
+                for horizon in horizons {
+                    configs.push(Config { n_arms: *n_arms, horizon: *horizon, reward_type: *reward_type });
+                }
+            }
+        }
+        Tournament { agents, configs, repetitions: 10, seed: 0, failure_threshold: 0.5, runner: Runner::new() }
+    }
+    pub fn run(&self) -> Result<(Vec<Curve>, Vec<Report>), String> {
+        let mut curves = vec![];
+        let mut reports = vec![];
+        for config in self.configs.iter() {
+            for name in self.agents.iter() {
+                let report = self.runner.repeat(name, config, self.repetitions, self.seed)?;
+                let rounds = checkpoints(config.horizon, self.runner.stride);
+                curves.push(Curve::from_runs(name, *config, rounds, &report.runs, self.failure_threshold));
+                reports.push(report);
+            }
+        }
+        Ok((curves, reports))
+    }
+}
+
+pub fn to_csv(curves: &[Curve]) -> String {
+    let mut out = String::from("agent,reward_type,n_arms,horizon,round,mean,lower,upper,lower_bound\n");
+    for c in curves {
+        for t in 0..c.mean.len() {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                c.agent, c.config.reward_type, c.config.n_arms, c.config.horizon,
+                c.rounds[t], c.mean[t], c.lower[t], c.upper[t],
+                c.lower_bound.as_ref().map(|b| b[t].to_string()).unwrap_or_default()
+            ));
+        }
+    }
+    out
+}
+
+pub fn to_json(curves: &[Curve]) -> Result<String, String> {
+    serde_json::to_string(curves).map_err(|e| e.to_string())
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+use std::vec::Vec;
+use serde::{Serialize, Deserialize};
+use crate::markov::MarkovArm;
+use crate::registry::SlotParams;
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DriftSpec {
+    pub at: usize,
+    #[serde(flatten)]
+    pub params: SlotParams,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComponentSpec {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(flatten)]
+    pub params: SlotParams,
+}
+
+impl ComponentSpec {
+    pub fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => format!("components[{}] ({})", index, name),
+            None => format!("components[{}]", index),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArmSpec {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub cost: f64,
+    #[serde(default)]
+    pub drift: Vec<DriftSpec>,
+    #[serde(default)]
+    pub components: Vec<ComponentSpec>,
+    #[serde(default)]
+    pub markov: Option<MarkovArm>,
+    #[serde(flatten)]
+    pub params: SlotParams,
+}
+
+impl ArmSpec {
+    pub fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => format!("arms[{}] ({})", index, name),
+            None => format!("arms[{}]", index),
+        }
+    }
+    pub fn phases(&self) -> Vec<(usize, SlotParams)> {
+        let mut current = self.params.clone();
+        let mut phases = vec![(0, current.clone())];
+        for step in self.drift.iter() {
+            current.extend(step.params.iter().map(|(k, v)| (k.clone(), *v)));
+            phases.push((step.at, current.clone()));
+        }
+        phases
+    }
+    pub fn dimension(&self) -> usize {
+        self.components.len().max(1)
+    }
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kind.is_empty() {
+            return Err("missing `type`".to_string())
+        }
+        if self.kind == "vector" {
+            if self.components.is_empty() {
+                return Err("a vector arm needs at least one component".to_string())
+            }
+            if !self.drift.is_empty() || !self.params.is_empty() {
+                return Err("a vector arm takes its parameters from `components` and does not support drift".to_string())
+            }
+        } else if !self.components.is_empty() {
+            return Err("`components` requires `type = \"vector\"`".to_string())
+        }
+        match (self.kind.as_str(), &self.markov) {
+            ("markov", Some(arm)) => {
+                if !self.drift.is_empty() || !self.params.is_empty() {
+                    return Err("a markov arm takes its parameters from `markov` and does not support drift".to_string())
+                }
+                arm.validate().map_err(|e| format!("markov: {}", e))?;
+            }
+            ("markov", None) => return Err("a markov arm needs a `markov` table".to_string()),
+            (_, Some(_)) => return Err("`markov` requires `type = \"markov\"`".to_string()),
+            _ => {}
+        }
+        for (i, component) in self.components.iter().enumerate() {
+            if component.kind.is_empty() {
+                return Err(format!("{}: missing `type`", component.label(i)))
+            }
+        }
+        if !(self.cost >= 0. && self.cost.is_finite()) {
+            return Err(format!("cost must be non-negative, got {}", self.cost))
+        }
+        let mut last = 0;
+        for (i, step) in self.drift.iter().enumerate() {
+            if step.at <= last {
+                return Err(format!("drift[{}]: `at` must be positive and increasing", i))
+            }
+            last = step.at;
+        }
+        Ok(())
+    }
+}
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GameSpec {
+    pub horizon: Option<usize>,
+    pub seed: Option<u64>,
+    pub arms: Vec<ArmSpec>,
+}
+
+impl GameSpec {
+    pub fn from_toml(text: &str) -> Result<GameSpec, String> {
+        let spec: GameSpec = toml::from_str(text).map_err(|e| e.to_string())?;
+        spec.validate()?;
+        Ok(spec)
+    }
+    pub fn from_json(text: &str) -> Result<GameSpec, String> {
+        let spec: GameSpec = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        spec.validate()?;
+        Ok(spec)
+    }
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GameSpec, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let spec = match path.extension().and_then(|x| x.to_str()) {
+            Some("json") => GameSpec::from_json(&text),
+            _ => GameSpec::from_toml(&text),
+        };
+        spec.map_err(|e| format!("{}: {}", path.display(), e))
+    }
+    pub fn validate(&self) -> Result<(), String> {
+        if self.arms.is_empty() {
+            return Err("at least one arm is required".to_string())
+        }
+        if self.horizon == Some(0) {
+            return Err("horizon must be positive".to_string())
+        }
+        for (i, arm) in self.arms.iter().enumerate() {
+            arm.validate().map_err(|e| format!("{}: {}", arm.label(i), e))?;
+            if arm.dimension() != self.arms[0].dimension() {
+                return Err(format!("{}: expected {} reward components like arms[0], got {}", arm.label(i), self.arms[0].dimension(), arm.dimension()))
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use crate::audit::{Action, AuditLog, Setup};
+use crate::game::Game;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Start(Option<usize>),
+    Pull(usize),
+    Score,
+    Reset,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Command, String> {
+        let mut words = s.split_whitespace();
+        let verb = words.next().unwrap_or("").to_ascii_uppercase();
+        let arg = words.next().map(|x| x.parse::<usize>().map_err(|_| format!("Invalid argument: {}", x))).transpose()?;
+        if words.next().is_some() {
+            return Err(format!("Too many arguments: {}", s.trim()))
+        }
+        match (verb.as_str(), arg) {
+            ("START", n) => Ok(Command::Start(n)),
+            ("PULL", Some(i)) => Ok(Command::Pull(i)),
+            ("PULL", None) => Err("PULL needs an arm index".to_string()),
+            ("SCORE", None) => Ok(Command::Score),
+            ("RESET", None) => Ok(Command::Reset),
+            ("QUIT", None) => Ok(Command::Quit),
+            ("SCORE", _) | ("RESET", _) | ("QUIT", _) => Err(format!("{} takes no argument", verb)),
+            _ => Err(format!("Unknown command: {}", s.trim()))
+        }
+    }
+}
+
+
+pub struct Session {
+    id: String,
+    game: Game,
+    audit: Arc<AuditLog>,
+}
+
+impl Session {
+    pub fn new(id: &str, setup: &Setup, audit: Arc<AuditLog>) -> Result<Session, String> {
+        let game = setup.build()?;
+        audit.append(id, Action::Create { setup: setup.clone() })?;
+        Ok(Session { id: id.to_string(), game, audit })
+    }
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+    pub fn handle(&mut self, command: Command) -> Result<String, String> {
+        match command {
+            Command::Start(n) => {
+                self.game.start(n);
+                self.audit.append(&self.id, Action::Start { n_games: n, mode: self.game.mode() })?;
+                Ok(format!("{}", self.game.n_machines()))
+            }
+            Command::Pull(i) => {
+                let reward = self.game.play(i)?;
+                self.audit.append(&self.id, Action::Play { arm: i, reward })?;
+                Ok(reward.to_string())
+            }
+            Command::Score => Ok(format!("{} {}", self.game.score(), self.game.play_count())),
+            Command::Reset => {
+                self.game.reset();
+                self.audit.append(&self.id, Action::Reset)?;
+                Ok(String::new())
+            }
+            Command::Quit => Ok(String::new()),
+        }
+    }
+    pub fn respond(&mut self, line: &str) -> (String, bool) {
+        let command = line.parse::<Command>();
+        let quit = command == Ok(Command::Quit);
+        let response = match command.and_then(|c| self.handle(c)) {
+            Ok(ref x) if x.is_empty() => "OK".to_string(),
+            Ok(x) => format!("OK {}", x),
+            Err(e) => format!("ERR {}", e),
+        };
+        (response, quit)
+    }
+}
+
+
+fn session(stream: TcpStream, mut session: Session) -> std::io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break
+        }
+        if line.trim().is_empty() {
+            continue
+        }
+        let (response, quit) = session.respond(&line);
+        writeln!(writer, "{}", response)?;
+        if quit {
+            break
+        }
+        if reader.buffer().is_empty() {
+            writer.flush()?;
+        }
+    }
+    writer.flush()
+}
+
+pub fn serve<A: ToSocketAddrs>(addr: A, factory: Arc<dyn Fn(usize) -> Setup + Send + Sync>, audit: Arc<AuditLog>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    for (id, stream) in listener.incoming().enumerate() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("accept: {}", e);
+                continue
+            }
+        };
+        let factory = factory.clone();
+        let audit = audit.clone();
+        thread::spawn(move || {
+            let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+            let name = format!("tcp-{}", id);
+            let result = Session::new(&name, &factory(id), audit)
+                .map_err(std::io::Error::other)
+                .and_then(|s| session(stream, s));
+            if let Err(e) = result {
+                eprintln!("session {} ({}): {}", id, peer, e);
+            }
+        });
+    }
+    Ok(())
+}